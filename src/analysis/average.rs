@@ -0,0 +1,121 @@
+//! Category weighted course averages
+
+use crate::model::grade::{GradeCalculationSummary, Mark};
+use serde::Serialize;
+
+// Row of the grade calculation summary holding the course total rather than a category
+const TOTAL_CATEGORY: &str = "TOTAL";
+
+/// Points earned in an assignment category along with its weight
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct CategoryAverage {
+    pub category: String,
+    /// Weight of the category as a percentage of the course grade, `None` for point based courses
+    pub weight: Option<f32>,
+    pub points: f32,
+    pub points_possible: f32,
+}
+
+impl CategoryAverage {
+    /// Percentage earned in the category, `None` if nothing has been graded yet
+    pub fn percent(&self) -> Option<f32> {
+        if self.points_possible > 0.0 {
+            Some(self.points / self.points_possible * 100.0)
+        } else {
+            None
+        }
+    }
+}
+
+/// Groups the graded assignments of a mark by category, categories without any graded work are kept with zero points
+pub fn category_averages(mark: &Mark) -> Vec<CategoryAverage> {
+    let scores = mark.assignments.assignment
+        .iter()
+        .filter_map(|a| Some((a.category.as_str(), a.points_earned()?, a.points_possible()?)));
+
+    averages_from_scores(&mark.grade_calculation, scores)
+}
+
+pub(crate) fn averages_from_scores<'a>(
+    summary: &GradeCalculationSummary,
+    scores: impl Iterator<Item = (&'a str, f32, f32)>,
+) -> Vec<CategoryAverage> {
+    let mut averages: Vec<CategoryAverage> = summary.category
+        .iter()
+        .filter(|c| !c.name.eq_ignore_ascii_case(TOTAL_CATEGORY))
+        .map(|c| CategoryAverage {
+            category: c.name.clone(),
+            weight: c.weight,
+            points: 0.0,
+            points_possible: 0.0,
+        })
+        .collect();
+
+    for (category, earned, possible) in scores {
+        let idx = match averages.iter().position(|a| a.category == category) {
+            Some(idx) => idx,
+            None => {
+                averages.push(CategoryAverage {
+                    category: category.to_owned(),
+                    weight: None,
+                    points: 0.0,
+                    points_possible: 0.0,
+                });
+                averages.len() - 1
+            }
+        };
+
+        averages[idx].points += earned;
+        averages[idx].points_possible += possible;
+    }
+
+    averages
+}
+
+/// Course percentage from its category averages
+///
+/// Weighted courses average each graded category by its weight, rescaling the weights of the categories
+/// which have graded work so they add up to 100%. Point based courses divide the total points earned by the
+/// total points possible.
+pub fn weighted_average(averages: &[CategoryAverage]) -> Option<f32> {
+    if averages.iter().any(|a| a.weight.is_some()) {
+        let (sum, weights) = averages
+            .iter()
+            .filter_map(|a| Some((a.weight?, a.percent()?)))
+            .fold((0.0, 0.0), |(sum, weights), (weight, percent)| (sum + weight * percent, weights + weight));
+
+        return if weights > 0.0 { Some(sum / weights) } else { None };
+    }
+
+    let (points, possible) = averages
+        .iter()
+        .fold((0.0, 0.0), |(p, pp), a| (p + a.points, pp + a.points_possible));
+
+    if possible > 0.0 { Some(points / possible * 100.0) } else { None }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::grade::GbData;
+    use quick_xml::de;
+
+    #[test]
+    fn weighted_and_point_based() {
+        let grades: GbData = de::from_str(include_str!("../../tests/fixtures/gradebook_assignments.xml")).unwrap();
+        let courses = &grades.gradebook.courses.course;
+
+        let chem = category_averages(courses[0].current_mark().unwrap());
+        assert_eq!(chem.len(), 2);
+        assert_eq!(chem[0].percent(), Some(80.0));
+        assert_eq!(chem[1].percent(), Some(95.0));
+        assert_eq!(weighted_average(&chem), Some(86.0));
+
+        let english = category_averages(courses[1].current_mark().unwrap());
+        assert_eq!(english.len(), 2);
+        assert_eq!(weighted_average(&english), Some(56.0 / 60.0 * 100.0));
+
+        let algebra = category_averages(courses[2].current_mark().unwrap());
+        assert_eq!(weighted_average(&algebra), None);
+    }
+}
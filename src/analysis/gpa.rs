@@ -0,0 +1,166 @@
+//! GPA calculation with configurable grade scales and course boosts
+
+use crate::model::grade::{Course, Gradebook};
+use regex::Regex;
+use serde::Serialize;
+
+/// Grade points awarded for each mark
+#[derive(Debug, Clone, PartialEq)]
+pub struct GpaScale {
+    marks: Vec<(String, f32)>,
+}
+
+impl GpaScale {
+    /// Creates a scale from mark and grade point pairs, marks are matched case insensitively
+    pub fn new(marks: &[(&str, f32)]) -> Self {
+        GpaScale {
+            marks: marks.iter().map(|(m, p)| (m.to_ascii_uppercase(), *p)).collect(),
+        }
+    }
+
+    /// Grade points of a mark, `None` for marks which do not count towards the GPA such as "P" or "N/A"
+    pub fn points(&self, mark: &str) -> Option<f32> {
+        let mark = mark.trim().to_ascii_uppercase();
+        self.marks.iter().find(|(m, _)| *m == mark).map(|(_, p)| *p)
+    }
+}
+
+impl Default for GpaScale {
+    /// Standard 4.0 scale with plus and minus marks
+    fn default() -> Self {
+        GpaScale::new(&[
+            ("A+", 4.0), ("A", 4.0), ("A-", 3.7),
+            ("B+", 3.3), ("B", 3.0), ("B-", 2.7),
+            ("C+", 2.3), ("C", 2.0), ("C-", 1.7),
+            ("D+", 1.3), ("D", 1.0), ("D-", 0.7),
+            ("F", 0.0), ("E", 0.0),
+        ])
+    }
+}
+
+/// Computes unweighted and weighted GPAs from the current marks of a `Gradebook`
+#[derive(Debug, Clone)]
+pub struct GpaCalculator {
+    scale: GpaScale,
+    boosts: Vec<(Regex, f32)>,
+}
+
+/// GPA of a gradebook, `None` when no course has a mark on the scale
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Gpa {
+    pub unweighted: Option<f32>,
+    pub weighted: Option<f32>,
+    pub courses: Vec<CourseGpa>,
+}
+
+/// Grade points a course contributes to the GPA
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct CourseGpa {
+    pub class_name: String,
+    pub mark: String,
+    pub points: f32,
+    /// Extra points added to `points` for the weighted GPA
+    pub boost: f32,
+}
+
+impl GpaCalculator {
+    /// Creates a calculator without any course boosts
+    pub fn new(scale: GpaScale) -> Self {
+        GpaCalculator {
+            scale,
+            boosts: Vec::new(),
+        }
+    }
+
+    /// Adds `boost` grade points to the weighted GPA of courses whose title matches the regex `pattern`,
+    /// when several patterns match the largest boost is used
+    pub fn with_boost(mut self, pattern: &str, boost: f32) -> Result<Self, regex::Error> {
+        self.boosts.push((Regex::new(pattern)?, boost));
+        Ok(self)
+    }
+
+    /// Grade points and boost of a course, `None` if its current mark is not on the scale
+    pub fn course(&self, course: &Course) -> Option<CourseGpa> {
+        let mark = course.current_mark()?;
+        let points = self.scale.points(&mark.grade)?;
+
+        // failing marks are never boosted
+        let boost = if points > 0.0 {
+            self.boosts
+                .iter()
+                .filter(|(pattern, _)| pattern.is_match(&course.class_name))
+                .map(|(_, boost)| *boost)
+                .fold(0.0, f32::max)
+        } else {
+            0.0
+        };
+
+        Some(CourseGpa {
+            class_name: course.class_name.clone(),
+            mark: mark.grade.clone(),
+            points,
+            boost,
+        })
+    }
+
+    /// Calculates the GPA over every course with a mark on the scale, each course counting equally
+    pub fn calculate(&self, gradebook: &Gradebook) -> Gpa {
+        let courses: Vec<CourseGpa> = gradebook.courses.course
+            .iter()
+            .filter_map(|c| self.course(c))
+            .collect();
+
+        let mean = |f: fn(&CourseGpa) -> f32| {
+            if courses.is_empty() {
+                None
+            } else {
+                Some(courses.iter().map(f).sum::<f32>() / courses.len() as f32)
+            }
+        };
+
+        Gpa {
+            unweighted: mean(|c| c.points),
+            weighted: mean(|c| c.points + c.boost),
+            courses,
+        }
+    }
+}
+
+impl Default for GpaCalculator {
+    /// Standard 4.0 scale with a full point boost for AP and IB courses and half a point for honors courses
+    fn default() -> Self {
+        GpaCalculator::new(GpaScale::default())
+            .with_boost(r"\b(AP|IB)\b", 1.0)
+            .and_then(|c| c.with_boost(r"(?i)\bhonors?\b", 0.5))
+            .expect("Default boost patterns are valid")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::grade::GbData;
+    use quick_xml::de;
+
+    #[test]
+    fn gpa() {
+        let grades: GbData = de::from_str(include_str!("../../tests/fixtures/gradebook_assignments.xml")).unwrap();
+        let gpa = GpaCalculator::default().calculate(&grades.gradebook);
+
+        // the pass/fail course is left out
+        assert_eq!(gpa.courses.len(), 3);
+        assert_eq!(gpa.courses[0].boost, 1.0);
+        assert_eq!(gpa.courses[1].boost, 0.5);
+        assert_eq!(gpa.courses[2].boost, 0.0);
+        assert_eq!(gpa.unweighted, Some((3.0 + 4.0 + 2.3) / 3.0));
+        assert_eq!(gpa.weighted, Some((4.0 + 4.5 + 2.3) / 3.0));
+    }
+
+    #[test]
+    fn custom_scale() {
+        let scale = GpaScale::new(&[("a", 5.0), ("b", 4.0)]);
+
+        assert_eq!(scale.points(" A "), Some(5.0));
+        assert_eq!(scale.points("C+"), None);
+    }
+}
@@ -0,0 +1,13 @@
+//! Calculations built on top of the gradebook model
+//!
+//! This module provides `gpa` to compute unweighted and weighted GPAs from a `Gradebook`, `average` to
+//! recompute course percentages from assignment data and the course's grade calculation categories, and
+//! `simulator` to answer "what do I need on the final?".
+
+pub mod average;
+pub mod gpa;
+pub mod simulator;
+
+pub use average::{category_averages, weighted_average, CategoryAverage};
+pub use gpa::{Gpa, GpaCalculator, GpaScale};
+pub use simulator::{HypotheticalAssignment, MarkCutoffs, WhatIf};
@@ -0,0 +1,195 @@
+//! What-if grade simulation
+//!
+//! `WhatIf` recalculates a course percentage from its assignments after adding hypothetical assignments or
+//! overriding scores, and solves for the score needed on an upcoming assignment to reach a target.
+
+use super::average::{averages_from_scores, weighted_average, CategoryAverage};
+use crate::model::grade::Mark;
+use std::collections::HashMap;
+
+/// Minimum percentage needed for each mark
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarkCutoffs {
+    cutoffs: Vec<(String, f32)>,
+}
+
+impl MarkCutoffs {
+    /// Creates cutoffs from mark and minimum percentage pairs
+    pub fn new(cutoffs: &[(&str, f32)]) -> Self {
+        let mut cutoffs: Vec<(String, f32)> = cutoffs.iter().map(|(m, p)| (m.to_ascii_uppercase(), *p)).collect();
+        cutoffs.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        MarkCutoffs { cutoffs }
+    }
+
+    /// Mark earned with a percentage
+    pub fn mark(&self, percent: f32) -> Option<&str> {
+        self.cutoffs.iter().find(|(_, min)| percent >= *min).map(|(m, _)| m.as_str())
+    }
+
+    /// Minimum percentage of a mark
+    pub fn minimum(&self, mark: &str) -> Option<f32> {
+        let mark = mark.trim().to_ascii_uppercase();
+        self.cutoffs.iter().find(|(m, _)| *m == mark).map(|(_, min)| *min)
+    }
+}
+
+impl Default for MarkCutoffs {
+    /// Standard 90/80/70/60 letter grade cutoffs
+    fn default() -> Self {
+        MarkCutoffs::new(&[("A", 90.0), ("B", 80.0), ("C", 70.0), ("D", 60.0), ("F", 0.0)])
+    }
+}
+
+/// Assignment which has not been posted yet
+#[derive(Debug, Clone, PartialEq)]
+pub struct HypotheticalAssignment {
+    pub name: String,
+    pub category: String,
+    pub points: f32,
+    pub points_possible: f32,
+}
+
+/// Hypothetical changes applied on top of the assignments of a course mark
+#[derive(Debug, Clone)]
+pub struct WhatIf<'m> {
+    mark: &'m Mark,
+    overrides: HashMap<String, f32>,
+    added: Vec<HypotheticalAssignment>,
+}
+
+impl<'m> WhatIf<'m> {
+    /// Starts a simulation from the assignments of a mark as currently graded
+    pub fn new(mark: &'m Mark) -> Self {
+        WhatIf {
+            mark,
+            overrides: HashMap::new(),
+            added: Vec::new(),
+        }
+    }
+
+    /// Adds a hypothetical assignment to a category, the category must be one of the course's grade
+    /// calculation categories for it to count towards a weighted course
+    pub fn add_assignment(mut self, name: &str, category: &str, points: f32, points_possible: f32) -> Self {
+        self.added.push(HypotheticalAssignment {
+            name: name.to_owned(),
+            category: category.to_owned(),
+            points,
+            points_possible,
+        });
+        self
+    }
+
+    /// Replaces the points earned on an existing assignment, identified by its gradebook id, or by its name
+    /// if the district sends no gradebook id; ungraded assignments become graded
+    pub fn override_score(mut self, assignment_id: &str, points: f32) -> Self {
+        self.overrides.insert(assignment_id.to_owned(), points);
+        self
+    }
+
+    /// The hypothetical assignments added so far
+    pub fn added(&self) -> &[HypotheticalAssignment] {
+        &self.added
+    }
+
+    /// Category averages after applying the changes
+    pub fn averages(&self) -> Vec<CategoryAverage> {
+        let existing = self.mark.assignments.assignment
+            .iter()
+            .filter_map(move |a| {
+                let id = if a.id.is_empty() { &a.name } else { &a.id };
+                let earned = self.overrides.get(id).copied().or_else(|| a.points_earned())?;
+                Some((a.category.as_str(), earned, a.points_possible()?))
+            });
+        let added = self.added
+            .iter()
+            .map(|a| (a.category.as_str(), a.points, a.points_possible));
+
+        averages_from_scores(&self.mark.grade_calculation, existing.chain(added))
+    }
+
+    /// Course percentage after applying the changes
+    pub fn percent(&self) -> Option<f32> {
+        weighted_average(&self.averages())
+    }
+
+    /// Course mark after applying the changes
+    pub fn mark<'c>(&self, cutoffs: &'c MarkCutoffs) -> Option<&'c str> {
+        cutoffs.mark(self.percent()?)
+    }
+
+    /// Points needed on an upcoming assignment worth `points_possible` in `category` for the course to reach
+    /// `target_percent`
+    ///
+    /// Returns `Some(0.0)` if the target is reached regardless of the score, a value above `points_possible`
+    /// if it can only be reached through extra credit, and `None` if the assignment cannot change the
+    /// course percentage.
+    pub fn required_score(&self, category: &str, points_possible: f32, target_percent: f32) -> Option<f32> {
+        // the course percentage is linear in the score of a single added assignment
+        let zero = self.clone().add_assignment("", category, 0.0, points_possible).percent()?;
+        let full = self.clone().add_assignment("", category, points_possible, points_possible).percent()?;
+
+        if zero >= target_percent {
+            return Some(0.0);
+        }
+        if full <= zero {
+            return None;
+        }
+
+        Some((target_percent - zero) / (full - zero) * points_possible)
+    }
+
+    /// Points needed on an upcoming assignment for the course to reach `target_mark`
+    pub fn required_score_for_mark(&self, category: &str, points_possible: f32, target_mark: &str, cutoffs: &MarkCutoffs) -> Option<f32> {
+        self.required_score(category, points_possible, cutoffs.minimum(target_mark)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::grade::GbData;
+    use quick_xml::de;
+
+    fn chemistry() -> Mark {
+        let grades: GbData = de::from_str(include_str!("../../tests/fixtures/gradebook_assignments.xml")).unwrap();
+        grades.gradebook.courses.course[0].current_mark().unwrap().clone()
+    }
+
+    #[test]
+    fn hypothetical_changes() {
+        let mark = chemistry();
+        assert_eq!(WhatIf::new(&mark).percent(), Some(86.0));
+
+        // 80/100 + 100/100 tests at 60%, 19/20 homework at 40%
+        let final_exam = WhatIf::new(&mark).override_score("103", 100.0);
+        assert_eq!(final_exam.percent(), Some(0.6 * 90.0 + 0.4 * 95.0));
+        assert_eq!(final_exam.mark(&MarkCutoffs::default()), Some("A"));
+
+        let homework = WhatIf::new(&mark).add_assignment("Worksheet", "Homework", 0.0, 20.0);
+        assert_eq!(homework.percent(), Some(0.6 * 80.0 + 0.4 * 47.5));
+        assert_eq!(homework.added()[0].name, "Worksheet");
+
+        // assignments without a gradebook id are overridden by name
+        let mut unnumbered = mark.clone();
+        unnumbered.assignments.assignment.iter_mut().for_each(|a| a.id.clear());
+        let final_exam = WhatIf::new(&unnumbered).override_score("Final Exam", 100.0);
+        assert_eq!(final_exam.percent(), Some(0.6 * 90.0 + 0.4 * 95.0));
+    }
+
+    #[test]
+    fn solve_for_target() {
+        let mark = chemistry();
+        let what_if = WhatIf::new(&mark);
+
+        // tests need to average 85% for the course to reach 89%: (80 + x) / 200 = 0.85
+        let needed = what_if.required_score("Tests", 100.0, 89.0).unwrap();
+        assert!((needed - 90.0).abs() < 1e-3);
+
+        // an A needs the tests to average 86.67%
+        let needed = what_if.required_score_for_mark("Tests", 100.0, "a", &MarkCutoffs::default()).unwrap();
+        assert!((needed - 93.333).abs() < 1e-2);
+        assert_eq!(what_if.required_score("Tests", 100.0, 50.0), Some(0.0));
+        assert_eq!(what_if.required_score("Participation", 10.0, 95.0), None);
+    }
+}
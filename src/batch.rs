@@ -0,0 +1,332 @@
+//! Fetching datasets for many accounts at once
+//!
+//! This module provides `BatchFetcher`, which retrieves the chosen datasets of every account concurrently
+//! and reports the outcome of each account and dataset on its own, so a single failing account does not
+//! fail the whole batch. Requests go through a shared `RateLimiter`, keeping accounts of the same district
+//! within its per-host limits, and a shared connection pool. An account whose login is rejected is not sent
+//! any further requests.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use studentvue::batch::{Account, BatchFetcher, Dataset};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let accounts = vec![
+//!         Account::new("https://studentvue.phoenixunion.org", "student1", "pwd1"),
+//!         Account::new("https://afsd.edupoint.com", "student2", "pwd2"),
+//!     ];
+//!     let results = BatchFetcher::new(&[Dataset::Grades, Dataset::Attendance])
+//!         .fetch(&accounts)
+//!         .await;
+//!
+//!     for result in results.iter() {
+//!         for err in result.errors() {
+//!             eprintln!("{}: {}", result.user, err);
+//!         }
+//!     }
+//! }
+//! ```
+
+use crate::{
+    client::Client,
+    error::VueError,
+    limit::RateLimiter,
+    model::{attendance, calendar, grade, message, schedule, school, student},
+    request::{Transport, WebHandle},
+    retry::RetryPolicy,
+};
+use futures_util::{future, stream, StreamExt};
+use serde::Serialize;
+use std::fmt;
+use zeroize::Zeroizing;
+
+/// Credentials of an account along with its district
+#[derive(Clone)]
+pub struct Account {
+    pub district_url: String,
+    pub user: String,
+    password: Zeroizing<String>,
+}
+
+impl fmt::Debug for Account {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Account")
+            .field("district_url", &self.district_url)
+            .field("user", &self.user)
+            .field("password", &"<redacted>")
+            .finish()
+    }
+}
+
+impl Account {
+    pub fn new(district_url: &str, user: &str, password: &str) -> Self {
+        Account {
+            district_url: district_url.to_owned(),
+            user: user.to_owned(),
+            password: Zeroizing::new(password.to_owned()),
+        }
+    }
+}
+
+/// Data which can be fetched for every account of a batch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Dataset {
+    Grades,
+    Attendance,
+    Schedule,
+    SchoolInfo,
+    StudentInfo,
+    Messages,
+    Calendar,
+}
+
+/// Fetched data of a `Dataset`
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(untagged)]
+pub enum DatasetData {
+    Grades(grade::GbData),
+    Attendance(attendance::AttData),
+    Schedule(schedule::StudentClassSchedule),
+    SchoolInfo(school::StudentSchoolInfoListing),
+    StudentInfo(Box<student::Student>),
+    Messages(message::PXPMessagesData),
+    Calendar(calendar::StudentCalendar),
+}
+
+/// Outcome of every requested dataset of an account, in the order the datasets were requested
+#[derive(Debug)]
+pub struct AccountResult {
+    pub district_url: String,
+    pub user: String,
+    pub datasets: Vec<(Dataset, Result<DatasetData, VueError>)>,
+}
+
+impl AccountResult {
+    /// Data of a dataset if it was fetched successfully
+    pub fn get(&self, dataset: Dataset) -> Option<&DatasetData> {
+        self.datasets.iter()
+            .find(|(d, _)| *d == dataset)
+            .and_then(|(_, res)| res.as_ref().ok())
+    }
+
+    /// Errors of the datasets which could not be fetched
+    pub fn errors(&self) -> impl Iterator<Item = &VueError> {
+        self.datasets.iter().filter_map(|(_, res)| res.as_ref().err())
+    }
+
+    /// Whether every dataset was fetched
+    pub fn is_ok(&self) -> bool {
+        self.errors().next().is_none()
+    }
+}
+
+/// Fetches datasets for many accounts concurrently
+#[derive(Debug, Clone)]
+pub struct BatchFetcher {
+    datasets: Vec<Dataset>,
+    max_concurrent: usize,
+    limiter: RateLimiter,
+    retry: RetryPolicy,
+}
+
+impl BatchFetcher {
+    /// Fetches `datasets` for up to 8 accounts at a time, sending at most 5 requests per second and 4 requests
+    /// at once to each district, retrying transient failures with the default `RetryPolicy`
+    pub fn new(datasets: &[Dataset]) -> Self {
+        BatchFetcher {
+            datasets: datasets.to_vec(),
+            max_concurrent: 8,
+            limiter: RateLimiter::new(5.0, 4),
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    /// Number of accounts fetched at the same time
+    pub fn with_max_concurrent(mut self, max_concurrent: usize) -> Self {
+        self.max_concurrent = max_concurrent.max(1);
+        self
+    }
+
+    /// Replaces the per-host limits, passing a limiter used elsewhere shares its limits with the batch
+    pub fn with_rate_limiter(mut self, limiter: RateLimiter) -> Self {
+        self.limiter = limiter;
+        self
+    }
+
+    pub fn with_retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// Fetches the datasets of every account, results are in the order of `accounts`
+    pub async fn fetch(&self, accounts: &[Account]) -> Vec<AccountResult> {
+        self.fetch_with_transport(accounts, WebHandle::new()).await
+    }
+
+    /// Fetches the datasets of every account through clones of `transport`, which should share their
+    /// connections like those of `WebHandle`
+    pub async fn fetch_with_transport<T: Transport + Clone>(&self, accounts: &[Account], transport: T) -> Vec<AccountResult> {
+        let clients: Vec<_> = accounts.iter()
+            .map(|a| {
+                Client::create_with_transport(&a.district_url, &a.user, &a.password, transport.clone())
+                    .with_rate_limiter(self.limiter.clone())
+                    .with_retry(self.retry.clone())
+            })
+            .collect();
+
+        self.fetch_clients(&clients).await
+    }
+
+    /// Fetches the datasets through already configured clients, their own limits and retry policies apply
+    pub async fn fetch_clients<T: Transport>(&self, clients: &[Client<T>]) -> Vec<AccountResult> {
+        stream::iter(clients)
+            .map(|client| self.fetch_account(client))
+            .buffered(self.max_concurrent)
+            .collect()
+            .await
+    }
+
+    async fn fetch_account<T: Transport>(&self, client: &Client<T>) -> AccountResult {
+        let mut datasets = Vec::with_capacity(self.datasets.len());
+
+        // the first dataset is fetched alone so a rejected login fails the others without sending them
+        if let Some((&first, rest)) = self.datasets.split_first() {
+            let result = fetch_dataset(client, first).await;
+            let others = match &result {
+                Err(VueError::Credentials(msg)) => {
+                    rest.iter().map(|&dataset| (dataset, Err(VueError::Credentials(msg.clone())))).collect()
+                }
+                _ => future::join_all(rest.iter().map(|&dataset| async move {
+                    (dataset, fetch_dataset(client, dataset).await)
+                }))
+                .await,
+            };
+
+            datasets.push((first, result));
+            datasets.extend(others);
+        }
+
+        AccountResult {
+            district_url: client.uri.to_string(),
+            user: client.user().to_owned(),
+            datasets,
+        }
+    }
+}
+
+async fn fetch_dataset<T: Transport>(client: &Client<T>, dataset: Dataset) -> Result<DatasetData, VueError> {
+    Ok(match dataset {
+        Dataset::Grades => DatasetData::Grades(client.get_grades(None).await?),
+        Dataset::Attendance => DatasetData::Attendance(client.get_attendance().await?),
+        Dataset::Schedule => DatasetData::Schedule(client.get_schedule(None, None).await?),
+        Dataset::SchoolInfo => DatasetData::SchoolInfo(client.get_school_info().await?),
+        Dataset::StudentInfo => DatasetData::StudentInfo(Box::new(client.get_student_info().await?)),
+        Dataset::Messages => DatasetData::Messages(client.get_messages().await?),
+        Dataset::Calendar => DatasetData::Calendar(client.get_calendar(None).await?),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::request::TransportFuture;
+    use std::{
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    // Serves fixtures by method and rejects the credentials of "locked", recording the users of the requests
+    #[derive(Default)]
+    struct District {
+        users: Mutex<Vec<String>>,
+    }
+
+    impl Transport for District {
+        type Future<'a> = TransportFuture<'a>;
+
+        fn post_form<'a>(&'a self, _uri: &'a str, params: &'a [(&'a str, &'a str)]) -> TransportFuture<'a> {
+            let param = |name| params.iter().find(|(k, _)| *k == name).unwrap().1;
+            self.users.lock().unwrap().push(param("userID").to_owned());
+            let body = match (param("userID"), param("methodName")) {
+                ("locked", _) => "<string><RT_ERROR ERROR_MESSAGE=\"Invalid user id or password\" /></string>",
+                (_, "Gradebook") => include_str!("../tests/fixtures/gradebook_assignments.xml"),
+                (_, "Attendance") => include_str!("../tests/fixtures/attendance_periods.xml"),
+                _ => "<string />",
+            };
+
+            Box::pin(async move { Ok(body.to_owned()) })
+        }
+    }
+
+    #[tokio::test]
+    async fn per_account_results() {
+        let district = Arc::new(District::default());
+        let clients: Vec<_> = ["student1", "locked", "student2"].iter()
+            .map(|user| Client::create_with_transport("https://sv.test", user, "pwd", district.clone()))
+            .collect();
+        let results = BatchFetcher::new(&[Dataset::Grades, Dataset::Attendance])
+            .with_max_concurrent(2)
+            .fetch_clients(&clients)
+            .await;
+
+        let users: Vec<_> = results.iter().map(|r| r.user.as_str()).collect();
+        assert_eq!(users, ["student1", "locked", "student2"]);
+
+        assert!(results[0].is_ok() && results[2].is_ok());
+        assert!(matches!(results[2].get(Dataset::Grades), Some(DatasetData::Grades(_))));
+        assert!(matches!(results[2].get(Dataset::Attendance), Some(DatasetData::Attendance(_))));
+
+        assert_eq!(results[1].errors().count(), 2);
+        assert!(results[1].errors().all(VueError::is_credentials));
+        assert_eq!(district.users.lock().unwrap().iter().filter(|u| *u == "locked").count(), 1);
+    }
+
+    // Fails the first request of every user with a connection reset and tracks the most requests in flight
+    #[derive(Default)]
+    struct Busy {
+        failed: Mutex<Vec<String>>,
+        in_flight: Mutex<(usize, usize)>,
+    }
+
+    impl Transport for Busy {
+        type Future<'a> = TransportFuture<'a>;
+
+        fn post_form<'a>(&'a self, _uri: &'a str, params: &'a [(&'a str, &'a str)]) -> TransportFuture<'a> {
+            Box::pin(async move {
+                {
+                    let mut in_flight = self.in_flight.lock().unwrap();
+                    in_flight.0 += 1;
+                    in_flight.1 = in_flight.1.max(in_flight.0);
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                self.in_flight.lock().unwrap().0 -= 1;
+
+                let user = params.iter().find(|(k, _)| *k == "userID").unwrap().1;
+                let mut failed = self.failed.lock().unwrap();
+                if failed.iter().any(|u| u == user) {
+                    Ok(include_str!("../tests/fixtures/gradebook_assignments.xml").to_owned())
+                } else {
+                    failed.push(user.to_owned());
+                    Err(std::io::Error::from(std::io::ErrorKind::ConnectionReset).into())
+                }
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn fetch_applies_limits() {
+        let accounts: Vec<_> = (0..6).map(|i| Account::new("https://sv.test", &format!("student{}", i), "pwd")).collect();
+        let busy = Arc::new(Busy::default());
+        let results = BatchFetcher::new(&[Dataset::Grades])
+            .with_rate_limiter(RateLimiter::new(1000.0, 2).with_burst(100))
+            .with_retry(RetryPolicy::new(2).with_backoff(Duration::from_millis(1), Duration::from_millis(1)))
+            .fetch_with_transport(&accounts, busy.clone())
+            .await;
+
+        assert!(results.iter().all(AccountResult::is_ok));
+        assert_eq!(busy.in_flight.lock().unwrap().1, 2);
+    }
+}
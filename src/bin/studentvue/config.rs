@@ -0,0 +1,119 @@
+//! Credential resolution from flags, environment, the config file and interactive prompts
+
+use serde::{Deserialize, Deserializer};
+use std::{
+    error::Error,
+    fs,
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+};
+use zeroize::Zeroizing;
+
+/// Contents of the config file, every field is optional
+///
+/// ```toml
+/// url = "https://studentvue.phoenixunion.org"
+/// user = "123456"
+/// password = "hunter2"
+/// ```
+#[derive(Default, Deserialize)]
+pub struct Config {
+    pub url: Option<String>,
+    pub user: Option<String>,
+    #[serde(default, deserialize_with = "zeroizing")]
+    pub password: Option<Zeroizing<String>>,
+}
+
+/// Credentials used to create a `Client`
+pub struct Credentials {
+    pub url: String,
+    pub user: String,
+    pub password: Zeroizing<String>,
+}
+
+fn zeroizing<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Zeroizing<String>>, D::Error> {
+    Ok(Option::<String>::deserialize(deserializer)?.map(Zeroizing::new))
+}
+
+/// Default config file location, `studentvue/config.toml` in the user's config directory
+pub fn default_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("studentvue").join("config.toml"))
+}
+
+impl Config {
+    /// Reads the config file at `path`, a missing file yields an empty config
+    pub fn load(path: Option<&Path>) -> Result<Self, Box<dyn Error>> {
+        let path = match path.map(Path::to_path_buf).or_else(default_path) {
+            Some(path) => path,
+            None => return Ok(Config::default()),
+        };
+
+        match fs::read_to_string(&path) {
+            Ok(contents) => Ok(toml::from_str(&contents)
+                .map_err(|e| format!("invalid config file {}: {}", path.display(), e))?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Fills in the credentials, preferring values given on the command line or through the environment and
+    /// prompting for anything still missing
+    pub fn resolve(self, url: Option<String>, user: Option<String>, password: Option<Zeroizing<String>>) -> Result<Credentials, Box<dyn Error>> {
+        let url = match url.or(self.url) {
+            Some(url) => url,
+            None => prompt("District url: ")?,
+        };
+        let user = match user.or(self.user) {
+            Some(user) => user,
+            None => prompt("Username: ")?,
+        };
+        let password = match password.or(self.password) {
+            Some(password) => password,
+            None => Zeroizing::new(rpassword::prompt_password("Password: ")?),
+        };
+
+        Ok(Credentials { url, user, password })
+    }
+}
+
+fn prompt(message: &str) -> io::Result<String> {
+    let mut stderr = io::stderr();
+    stderr.write_all(message.as_bytes())?;
+    stderr.flush()?;
+
+    let mut line = String::new();
+    io::stdin().lock().read_line(&mut line)?;
+
+    Ok(line.trim().to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> Config {
+        toml::from_str("url = \"https://config.test\"\nuser = \"config-user\"\npassword = \"config-pwd\"").unwrap()
+    }
+
+    #[test]
+    fn arguments_override_config() {
+        let credentials = config()
+            .resolve(Some(String::from("https://flag.test")), Some(String::from("flag-user")), Some(Zeroizing::new(String::from("env-pwd"))))
+            .unwrap();
+
+        assert_eq!(credentials.url, "https://flag.test");
+        assert_eq!(credentials.user, "flag-user");
+        assert_eq!(credentials.password.as_str(), "env-pwd");
+    }
+
+    #[test]
+    fn config_fills_missing_arguments() {
+        let credentials = config()
+            .resolve(None, Some(String::from("flag-user")), None)
+            .unwrap();
+
+        assert_eq!(credentials.url, "https://config.test");
+        assert_eq!(credentials.user, "flag-user");
+        assert_eq!(credentials.password.as_str(), "config-pwd");
+    }
+}
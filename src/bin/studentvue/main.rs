@@ -0,0 +1,256 @@
+//! Command line interface to the StudentVUE service
+//!
+//! Credentials are taken from the command line and environment (`STUDENTVUE_URL`, `STUDENTVUE_USER`,
+//! `STUDENTVUE_PASSWORD`), then the config file, and are prompted for when still missing.
+
+mod config;
+mod table;
+
+use clap::{Parser, Subcommand};
+use serde::Serialize;
+use std::{error::Error, path::PathBuf, process};
+use studentvue::{
+    client::{Client, ParamBuilder},
+    enums::{Method, WebServiceHandle},
+    model::date::Date,
+    retry::RetryPolicy,
+};
+use table::{opt, Table};
+use zeroize::Zeroizing;
+
+#[derive(Parser)]
+#[command(name = "studentvue", version, about = "Query the StudentVUE service from the command line")]
+struct Cli {
+    /// District StudentVUE url, e.g. https://studentvue.phoenixunion.org
+    #[arg(long, env = "STUDENTVUE_URL", global = true)]
+    url: Option<String>,
+    /// StudentVUE username
+    #[arg(long, short, env = "STUDENTVUE_USER", global = true)]
+    user: Option<String>,
+    /// Config file, defaults to studentvue/config.toml in the user's config directory
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+    /// Print JSON instead of tables
+    #[arg(long, global = true)]
+    json: bool,
+    /// Attempts made for requests failing with transient errors
+    #[arg(long, global = true, default_value_t = 3)]
+    retries: u32,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Course marks of the current or a given reporting period
+    Grades {
+        /// Reporting period index
+        #[arg(long)]
+        period: Option<u64>,
+    },
+    /// Absences
+    Attendance,
+    /// Class schedule of the current or a given term
+    Schedule {
+        /// Term index
+        #[arg(long)]
+        term: Option<u8>,
+    },
+    /// Information about the attended school
+    School,
+    /// Student profile
+    Student,
+    /// Calendar of the current month or the month containing a date
+    Calendar {
+        /// Any date of the month, e.g. 9/14/2019
+        #[arg(long)]
+        date: Option<Date>,
+    },
+    /// Messages sent to the student
+    Messages,
+    /// Calls a method by name and prints the XML response, or its JSON conversion with --json
+    Raw {
+        /// Method name, e.g. StudentHealthInfo
+        method: String,
+        /// Web service handle, defaults to the handle serving the method
+        #[arg(long)]
+        handle: Option<String>,
+        /// Parameter passed to the method, may be repeated
+        #[arg(long = "param", value_name = "NAME=VALUE", value_parser = parse_param)]
+        params: Vec<(String, String)>,
+        /// Print the response as an indented tree
+        #[arg(long)]
+        tree: bool,
+    },
+}
+
+#[tokio::main]
+async fn main() {
+    if let Err(e) = run(Cli::parse()).await {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    }
+}
+
+async fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
+    let password = std::env::var("STUDENTVUE_PASSWORD").ok().map(Zeroizing::new);
+    let credentials = config::Config::load(cli.config.as_deref())?
+        .resolve(cli.url, cli.user, password)?;
+    let client = Client::create(&credentials.url, &credentials.user, &credentials.password)
+        .with_retry(RetryPolicy::new(cli.retries));
+    let json = cli.json;
+
+    match cli.command {
+        Command::Grades { period } => {
+            let grades = client.get_grades(period).await?;
+            output(json, &grades, || {
+                let mut table = Table::new(&["Period", "Course", "Room", "Teacher", "Mark", "Percent"]);
+                for course in grades.gradebook.courses.course.iter() {
+                    let mark = course.current_mark();
+                    table.row(vec![
+                        course.period.to_string(),
+                        course.class_name.clone(),
+                        course.room.clone(),
+                        course.staff.clone(),
+                        opt(mark.map(|m| &m.grade)),
+                        opt(mark.and_then(|m| m.percent).map(|p| format!("{:.1}", p))),
+                    ]);
+                }
+                table.print();
+            })
+        }
+        Command::Attendance => {
+            let attendance = client.get_attendance().await?;
+            output(json, &attendance, || {
+                let mut table = Table::new(&["Date", "Reason", "Periods", "Note"]);
+                for absence in attendance.attendance.absences.absence.iter() {
+                    let periods: Vec<String> = absence.periods.period.iter().map(|p| p.number.to_string()).collect();
+                    table.row(vec![
+                        absence.absence_date.to_string(),
+                        absence.reason.clone(),
+                        periods.join(","),
+                        absence.note.clone(),
+                    ]);
+                }
+                table.print();
+            })
+        }
+        Command::Schedule { term } => {
+            let schedule = client.get_schedule(term, None).await?;
+            output(json, &schedule, || {
+                let mut table = Table::new(&["Period", "Course", "Room", "Teacher"]);
+                for class in schedule.schedule.class_list.class.iter() {
+                    table.row(vec![
+                        class.period.to_string(),
+                        class.course_title.clone(),
+                        class.room_name.clone(),
+                        class.teacher.clone(),
+                    ]);
+                }
+                table.print();
+            })
+        }
+        Command::School => {
+            let school = client.get_school_info().await?;
+            output(json, &school, || {
+                let info = &school.school_info;
+                table::fields(&[
+                    ("School", info.school_name.clone()),
+                    ("Principal", info.principal.clone()),
+                    ("Address", format!("{}, {}, {} {}", info.school_address, info.school_city, info.school_state, info.school_zip)),
+                    ("Phone", info.phone.clone()),
+                    ("Website", info.url.clone()),
+                ]);
+            })
+        }
+        Command::Student => {
+            let student = client.get_student_info().await?;
+            output(json, &student, || {
+                let info = &student.student_info;
+                table::fields(&[
+                    ("Name", info.name.clone()),
+                    ("ID", info.id.to_string()),
+                    ("Grade", info.grade.to_string()),
+                    ("Birth date", opt(info.birth_date)),
+                    ("School", info.current_school.clone()),
+                    ("Email", info.email.clone()),
+                    ("Phone", info.phone.clone()),
+                    ("Counselor", info.counselor_name.clone()),
+                ]);
+            })
+        }
+        Command::Calendar { date } => {
+            let calendar = client.get_calendar(date).await?;
+            output(json, &calendar, || {
+                let mut table = Table::new(&["Date", "Time", "Type", "Title"]);
+                for event in calendar.calendar.event_lists.event.iter() {
+                    table.row(vec![
+                        event.date.to_string(),
+                        opt(event.start_time),
+                        event.day_type.clone(),
+                        event.title.clone(),
+                    ]);
+                }
+                table.print();
+            })
+        }
+        Command::Messages => {
+            let messages = client.get_messages().await?;
+            output(json, &messages, || {
+                let mut table = Table::new(&["Date", "From", "Subject", "Read"]);
+                for message in messages.messages.message_listings.message.iter() {
+                    table.row(vec![
+                        opt(message.begin_date),
+                        message.from.clone(),
+                        message.subject.clone(),
+                        if message.read { "yes" } else { "no" }.to_owned(),
+                    ]);
+                }
+                table.print();
+            })
+        }
+        Command::Raw { method, handle, params, tree } => {
+            // known methods are sent with their canonical name and handle
+            let known = method.parse::<Method>().ok();
+            let method = match known {
+                Some(known) => known.into(),
+                None => method.as_str(),
+            };
+            let handle = handle.unwrap_or_else(|| {
+                let handle = known.map_or(WebServiceHandle::PXPWebServices, Method::web_service_handle);
+                <&str>::from(handle).to_owned()
+            });
+
+            let mut parms = ParamBuilder::create();
+            for (name, value) in params.iter() {
+                parms.add_param(name, value)?;
+            }
+
+            if json {
+                let tree = client.call_raw_tree(&handle, method, parms).await?;
+                println!("{}", serde_json::to_string_pretty(&tree.to_json())?);
+            } else if tree {
+                print!("{}", client.call_raw_tree(&handle, method, parms).await?);
+            } else {
+                println!("{}", client.call_raw(&handle, method, parms).await?);
+            }
+            Ok(())
+        }
+    }
+}
+
+fn parse_param(param: &str) -> Result<(String, String), String> {
+    param.split_once('=')
+        .map(|(name, value)| (name.to_owned(), value.to_owned()))
+        .ok_or_else(|| format!("expected NAME=VALUE, found {}", param))
+}
+
+fn output<T: Serialize>(json: bool, data: &T, table: impl FnOnce()) -> Result<(), Box<dyn Error>> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(data)?);
+    } else {
+        table();
+    }
+
+    Ok(())
+}
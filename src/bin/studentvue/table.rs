@@ -0,0 +1,85 @@
+//! Plain text table output
+
+/// Column aligned table printed to stdout
+pub struct Table {
+    headers: Vec<&'static str>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    pub fn new(headers: &[&'static str]) -> Self {
+        Table { headers: headers.to_vec(), rows: Vec::new() }
+    }
+
+    pub fn row(&mut self, row: Vec<String>) -> &mut Self {
+        self.rows.push(row);
+        self
+    }
+
+    pub fn print(&self) {
+        print!("{}", self.render());
+    }
+
+    /// Formats the table, one line per row below the headers and a separator
+    pub fn render(&self) -> String {
+        let mut widths: Vec<usize> = self.headers.iter().map(|h| h.chars().count()).collect();
+        for row in self.rows.iter() {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let mut out = String::new();
+        let mut line = |cells: Vec<&str>| {
+            let padded: Vec<String> = cells.iter()
+                .zip(widths.iter())
+                .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                .collect();
+
+            out.push_str(padded.join("  ").trim_end());
+            out.push('\n');
+        };
+
+        line(self.headers.clone());
+        line(widths.iter().map(|w| "-".repeat(*w)).collect::<Vec<_>>().iter().map(String::as_str).collect());
+        for row in self.rows.iter() {
+            line(row.iter().map(String::as_str).collect());
+        }
+
+        out
+    }
+}
+
+/// Two column table of field names and values
+pub fn fields(fields: &[(&'static str, String)]) {
+    let mut table = Table::new(&["Field", "Value"]);
+    for (name, value) in fields.iter().filter(|(_, v)| !v.is_empty()) {
+        table.row(vec![name.to_string(), value.clone()]);
+    }
+
+    table.print();
+}
+
+/// Formats an optional value, leaving the cell empty when missing
+pub fn opt<T: ToString>(value: Option<T>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aligned_columns() {
+        let mut table = Table::new(&["Period", "Course", "Mark"]);
+        table.row(vec![String::from("1"), String::from("AP Chemistry"), String::from("A")])
+            .row(vec![String::from("10"), String::from("Art"), opt(None::<f32>)]);
+
+        assert_eq!(table.render(), "\
+Period  Course        Mark
+------  ------------  ----
+1       AP Chemistry  A
+10      Art
+");
+    }
+}
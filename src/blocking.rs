@@ -0,0 +1,226 @@
+//! Synchronous StudentVUE client
+//!
+//! This module provides a blocking `Client` which mirrors the typed getters of the asynchronous
+//! [Client](../client/struct.Client.html) by driving each request on an internal single threaded runtime.
+//! It is enabled with the `blocking` feature.
+//!
+//! The blocking `Client` must not be used from within an asynchronous runtime as blocking on a request
+//! would stall the runtime's executor.
+
+use crate::{
+    client::{self, ParamBuilder},
+    enums::*,
+    model::*,
+    error::VueError,
+    request::{Transport, WebHandle},
+    xml,
+};
+use std::{
+    fmt,
+    sync::Arc,
+};
+use tokio::runtime::{Builder, Runtime};
+
+/// Struct which connects to the StudentVUE service and blocks on every request
+pub struct Client<T: Transport = WebHandle> {
+    inner: client::Client<T>,
+    runtime: Arc<Runtime>,
+}
+
+impl<T: Transport> Clone for Client<T> {
+    fn clone(&self) -> Self {
+        Client {
+            inner: self.inner.clone(),
+            runtime: self.runtime.clone(),
+        }
+    }
+}
+
+impl<T: Transport> fmt::Debug for Client<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Client")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+impl Client {
+    /// Instantiates a new blocking `Client` with the username, password, and corresponding StudentVUE district url
+    pub fn create(district_url: &str, username: &str, password: &str) -> Result<Self, VueError> {
+        Client::from_async(client::Client::create(district_url, username, password))
+    }
+
+    /// Instantiates a blocking `Client` for Edupoint's district lookup service
+    pub fn district_lookup() -> Result<Self, VueError> {
+        Client::from_async(client::Client::district_lookup())
+    }
+}
+
+impl<T: Transport> Client<T> {
+    /// Instantiates a new blocking `Client` which sends its requests through the given `Transport`
+    pub fn create_with_transport(district_url: &str, username: &str, password: &str, transport: T) -> Result<Self, VueError> {
+        Client::from_async(client::Client::create_with_transport(district_url, username, password, transport))
+    }
+
+    /// Wraps an existing asynchronous `Client`
+    pub fn from_async(inner: client::Client<T>) -> Result<Self, VueError> {
+        let runtime = Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+
+        Ok(Client {
+            inner,
+            runtime: Arc::new(runtime),
+        })
+    }
+
+    /// The asynchronous `Client` requests are made through
+    #[inline]
+    pub fn as_async(&self) -> &client::Client<T> {
+        &self.inner
+    }
+
+    /// Username the `Client` authenticates with
+    #[inline]
+    pub fn user(&self) -> &str {
+        self.inner.user()
+    }
+
+    /// Calls a method with the specified parameters through the `WebServiceHandle` the method belongs to
+    #[inline]
+    pub fn call_service(&self, method_name: Method, param_str: ParamBuilder) -> Result<String, VueError> {
+        self.runtime.block_on(self.inner.call_service(method_name, param_str))
+    }
+
+    /// Calls a method from a specified `WebServiceHandle` with the specified parameters
+    #[inline]
+    pub fn call_service_with_handle(
+        &self,
+        web_service_handle: WebServiceHandle,
+        method_name: Method,
+        param_str: ParamBuilder,
+    ) -> Result<String, VueError> {
+        self.runtime.block_on(self.inner.call_service_with_handle(web_service_handle, method_name, param_str))
+    }
+
+    /// Calls a method, parsing the response into a generic XML tree for methods without a typed model
+    #[inline]
+    pub fn call_service_tree(&self, method_name: Method, param_str: ParamBuilder) -> Result<xml::Element, VueError> {
+        self.runtime.block_on(self.inner.call_service_tree(method_name, param_str))
+    }
+
+    /// Calls a method by name through a web service handle given by name
+    #[inline]
+    pub fn call_raw(&self, web_service_handle: &str, method_name: &str, param_str: ParamBuilder) -> Result<String, VueError> {
+        self.runtime.block_on(self.inner.call_raw(web_service_handle, method_name, param_str))
+    }
+
+    /// Calls a method by name, parsing the response into a generic XML tree
+    #[inline]
+    pub fn call_raw_tree(&self, web_service_handle: &str, method_name: &str, param_str: ParamBuilder) -> Result<xml::Element, VueError> {
+        self.runtime.block_on(self.inner.call_raw_tree(web_service_handle, method_name, param_str))
+    }
+
+    /// Retrieves grades from a student; can be current or from a specified reporting period
+    #[inline]
+    pub fn get_grades(&self, report_period: Option<u64>) -> Result<grade::GbData, VueError> {
+        self.runtime.block_on(self.inner.get_grades(report_period))
+    }
+
+    /// Lists every reporting period the server advertises, across all terms
+    #[inline]
+    pub fn get_reporting_periods(&self) -> Result<Vec<grade::ReportPeriod>, VueError> {
+        self.runtime.block_on(self.inner.get_reporting_periods())
+    }
+
+    /// Retrieves the gradebook of every advertised reporting period, requesting at most `max_concurrent`
+    /// periods at a time
+    #[inline]
+    pub fn get_all_grades(&self, max_concurrent: usize) -> Result<grade::MultiPeriodGradebook, VueError> {
+        self.runtime.block_on(self.inner.get_all_grades(max_concurrent))
+    }
+
+    /// Gets the absences from the student
+    #[inline]
+    pub fn get_attendance(&self) -> Result<attendance::AttData, VueError> {
+        self.runtime.block_on(self.inner.get_attendance())
+    }
+
+    /// Retrieves student information such as their name, address, and grade
+    #[inline]
+    pub fn get_student_info(&self) -> Result<student::Student, VueError> {
+        self.runtime.block_on(self.inner.get_student_info())
+    }
+
+    /// Retrieves the student's school schedule; can be the current term or a specified term index, optionally
+    /// for a concurrently attended school identified by its `ConcurrentSchOrgYearGU`
+    #[inline]
+    pub fn get_schedule(&self, term_index: Option<u8>, concurrent_school: Option<&str>) -> Result<schedule::StudentClassSchedule, VueError> {
+        self.runtime.block_on(self.inner.get_schedule(term_index, concurrent_school))
+    }
+
+    /// Grabs information about the currently attended school
+    #[inline]
+    pub fn get_school_info(&self) -> Result<school::StudentSchoolInfoListing, VueError> {
+        self.runtime.block_on(self.inner.get_school_info())
+    }
+
+    /// Retrieves the messages sent to the student
+    #[inline]
+    pub fn get_messages(&self) -> Result<message::PXPMessagesData, VueError> {
+        self.runtime.block_on(self.inner.get_messages())
+    }
+
+    /// Retrieves the calendar of the month containing `date`, or the current month
+    #[inline]
+    pub fn get_calendar(&self, date: Option<date::Date>) -> Result<calendar::StudentCalendar, VueError> {
+        self.runtime.block_on(self.inner.get_calendar(date))
+    }
+
+    /// Registers a push notification device token so the district can notify the device
+    #[inline]
+    pub fn register_device_token(&self, token: &str, platform: Platform) -> Result<device::DeviceTokenResponse, VueError> {
+        self.runtime.block_on(self.inner.register_device_token(token, platform))
+    }
+
+    /// Removes a previously registered push notification device token
+    #[inline]
+    pub fn unregister_device_token(&self, token: &str, platform: Platform) -> Result<device::DeviceTokenResponse, VueError> {
+        self.runtime.block_on(self.inner.unregister_device_token(token, platform))
+    }
+
+    /// Looks up the districts matching a zip code, this must be called on `Client::district_lookup`
+    #[inline]
+    pub fn get_matching_districts(&self, zip_code: &str) -> Result<district::MatchingDistricts, VueError> {
+        self.runtime.block_on(self.inner.get_matching_districts(zip_code))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        io::{Read, Write},
+        net::TcpListener,
+        thread,
+    };
+
+    #[test]
+    fn blocking_request() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let uri = format!("http://{}", listener.local_addr().unwrap());
+
+        thread::spawn(move || {
+            let body = "<string>&lt;DistrictLists&gt;&lt;DistrictInfos&gt;&lt;DistrictInfo DistrictID=\"1\" Name=\"Test\" Address=\"Phoenix AZ\" PvueURL=\"https://sv.test\" /&gt;&lt;/DistrictInfos&gt;&lt;/DistrictLists&gt;</string>";
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 4096];
+            let _ = stream.read(&mut buf).unwrap();
+            write!(stream, "HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}", body.len(), body).unwrap();
+        });
+
+        let client = Client::create(&uri, "student", "pwd").unwrap();
+        let districts = client.get_matching_districts("85001").unwrap();
+
+        assert_eq!(districts.lists.infos.district[0].name, "Test");
+    }
+}
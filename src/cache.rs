@@ -0,0 +1,456 @@
+//! Response caching
+//!
+//! This module provides `Cache`, which `Client::with_cache` places in front of every service call. Responses
+//! are keyed on the district, credentials, web service handle, method and parameters of the call, so a client
+//! with a changed or wrong password never reads another login's responses, and are kept for a duration
+//! chosen per `Method` by a `CachePolicy`. Storage is pluggable through the `CacheStore` trait, with the
+//! in-memory `MemoryStore` and the on-disk `DiskStore` provided.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use studentvue::{
+//!     cache::{Cache, CachePolicy, DiskStore},
+//!     client::Client,
+//!     enums::Method,
+//! };
+//! use std::time::Duration;
+//!
+//! let policy = CachePolicy::default()
+//!     .with_ttl(Method::GradeBook, Duration::from_secs(60));
+//! let cache = Cache::new(DiskStore::new("studentvue-cache").unwrap(), policy);
+//! let client = Client::create("https://studentvue.phoenixunion.org", "user", "pwd")
+//!     .with_cache(cache);
+//! ```
+
+use crate::enums::Method;
+use hmac::{Hmac, Mac};
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::{
+    collections::HashMap,
+    fmt,
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+/// Identifies a cached response
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CacheKey {
+    /// Base url of the district
+    pub uri: String,
+    pub user: String,
+    /// Hex encoded HMAC of the user and password, see `credential_hash`
+    pub credential: String,
+    pub handle: String,
+    pub method: String,
+    /// Parameter string sent with the call
+    pub params: String,
+}
+
+lazy_static! {
+    /// Key of `credential_hash`, generated once per process and never written anywhere
+    static ref CREDENTIAL_SECRET: [u8; 32] = {
+        let mut secret = [0; 32];
+        getrandom::getrandom(&mut secret).expect("no source of randomness for the cache key secret");
+        secret
+    };
+}
+
+/// Hash identifying a user and password pair in a `CacheKey` without storing the password
+///
+/// This is an HMAC-SHA256 keyed with a random secret which only lives in memory for the life of the process,
+/// so a hash read from a cache can't be checked against guessed passwords, and hashes differ between runs.
+pub fn credential_hash(user: &str, password: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(&*CREDENTIAL_SECRET).expect("HMAC accepts keys of any length");
+    mac.update(user.as_bytes());
+    mac.update(&[0]);
+    mac.update(password.as_bytes());
+
+    mac.finalize().into_bytes().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Cached response along with the time it was stored
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub body: String,
+    pub stored_at: SystemTime,
+}
+
+/// Storage backing a `Cache`
+///
+/// Stores are shared between clones of a `Client` and may be called from several tasks at once.
+pub trait CacheStore: Send + Sync + 'static {
+    fn get(&self, key: &CacheKey) -> Option<CacheEntry>;
+
+    fn put(&self, key: &CacheKey, entry: CacheEntry);
+
+    /// Removes the entry of `key`, by default through `remove_where`
+    fn remove(&self, key: &CacheKey) {
+        self.remove_where(&|stored| stored == key)
+    }
+
+    /// Removes every entry whose key `matches` returns true for
+    fn remove_where(&self, matches: &dyn Fn(&CacheKey) -> bool);
+}
+
+/// Time to live of cached responses per `Method`, methods without a time to live are never cached
+#[derive(Debug, Clone, PartialEq)]
+pub struct CachePolicy {
+    ttls: HashMap<Method, Duration>,
+}
+
+impl CachePolicy {
+    /// Policy which caches nothing
+    pub fn new() -> Self {
+        CachePolicy { ttls: HashMap::new() }
+    }
+
+    /// Caches responses of `method` for `ttl`
+    pub fn with_ttl(mut self, method: Method, ttl: Duration) -> Self {
+        self.ttls.insert(method, ttl);
+        self
+    }
+
+    /// Stops caching responses of `method`
+    pub fn without(mut self, method: Method) -> Self {
+        self.ttls.remove(&method);
+        self
+    }
+
+    /// Time to live of `method`, `None` if it is not cached
+    pub fn ttl(&self, method: Method) -> Option<Duration> {
+        self.ttls.get(&method).copied()
+    }
+}
+
+impl Default for CachePolicy {
+    /// Caches the read-only getters, school and student information for a day and frequently changing data
+    /// such as the gradebook for a few minutes
+    fn default() -> Self {
+        const MINUTE: u64 = 60;
+        const HOUR: u64 = 60 * MINUTE;
+
+        [
+            (Method::StudentSchoolInfo, 24 * HOUR),
+            (Method::StudentInfo, 24 * HOUR),
+            (Method::GetMatchingDistrictList, 24 * HOUR),
+            (Method::StudentClassList, HOUR),
+            (Method::StudentCalendar, HOUR),
+            (Method::Attendance, 15 * MINUTE),
+            (Method::GradeBook, 5 * MINUTE),
+            (Method::GetPXPMessages, 5 * MINUTE),
+        ]
+        .iter()
+        .fold(CachePolicy::new(), |policy, &(method, secs)| policy.with_ttl(method, Duration::from_secs(secs)))
+    }
+}
+
+/// Response cache used by `Client`
+pub struct Cache {
+    store: Box<dyn CacheStore>,
+    policy: CachePolicy,
+}
+
+impl fmt::Debug for Cache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cache")
+            .field("policy", &self.policy)
+            .finish()
+    }
+}
+
+impl Cache {
+    pub fn new(store: impl CacheStore, policy: CachePolicy) -> Self {
+        Cache { store: Box::new(store), policy }
+    }
+
+    /// The policy deciding which methods are cached
+    pub fn policy(&self) -> &CachePolicy {
+        &self.policy
+    }
+
+    /// Time to live of a method given by name, unknown methods are not cached
+    pub(crate) fn ttl_of(&self, method: &str) -> Option<Duration> {
+        self.policy.ttl(method.parse().ok()?)
+    }
+
+    /// Cached response for `key` if it is younger than `ttl`, older entries are removed from the store
+    pub(crate) fn get(&self, key: &CacheKey, ttl: Duration) -> Option<String> {
+        let entry = self.store.get(key)?;
+        let age = entry.stored_at.elapsed().unwrap_or(Duration::MAX);
+
+        if age <= ttl {
+            Some(entry.body)
+        } else {
+            self.store.remove(key);
+            None
+        }
+    }
+
+    pub(crate) fn put(&self, key: &CacheKey, body: String) {
+        self.store.put(key, CacheEntry { body, stored_at: SystemTime::now() });
+    }
+
+    /// Drops the cached responses of a method for every user
+    pub fn invalidate_method(&self, method: Method) {
+        let name = <&str>::from(method);
+        self.store.remove_where(&|key| key.method.eq_ignore_ascii_case(name));
+    }
+
+    /// Drops the cached responses of a method for a user of a district
+    pub fn invalidate(&self, uri: &str, user: &str, method: Method) {
+        let name = <&str>::from(method);
+        self.store.remove_where(&|key| key.uri == uri && key.user == user && key.method.eq_ignore_ascii_case(name));
+    }
+
+    /// Drops every cached response of a user
+    pub fn invalidate_user(&self, user: &str) {
+        self.store.remove_where(&|key| key.user == user);
+    }
+
+    /// Drops every cached response
+    pub fn clear(&self) {
+        self.store.remove_where(&|_| true);
+    }
+}
+
+/// Lets several caches, e.g. of clients for different accounts, share one store
+impl<S: CacheStore> CacheStore for Arc<S> {
+    fn get(&self, key: &CacheKey) -> Option<CacheEntry> {
+        (**self).get(key)
+    }
+
+    fn put(&self, key: &CacheKey, entry: CacheEntry) {
+        (**self).put(key, entry)
+    }
+
+    fn remove(&self, key: &CacheKey) {
+        (**self).remove(key)
+    }
+
+    fn remove_where(&self, matches: &dyn Fn(&CacheKey) -> bool) {
+        (**self).remove_where(matches)
+    }
+}
+
+/// In-memory `CacheStore`
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    entries: Mutex<HashMap<CacheKey, CacheEntry>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        MemoryStore::default()
+    }
+}
+
+impl CacheStore for MemoryStore {
+    fn get(&self, key: &CacheKey) -> Option<CacheEntry> {
+        self.entries.lock().unwrap().get(key).cloned()
+    }
+
+    fn put(&self, key: &CacheKey, entry: CacheEntry) {
+        self.entries.lock().unwrap().insert(key.clone(), entry);
+    }
+
+    fn remove(&self, key: &CacheKey) {
+        self.entries.lock().unwrap().remove(key);
+    }
+
+    fn remove_where(&self, matches: &dyn Fn(&CacheKey) -> bool) {
+        self.entries.lock().unwrap().retain(|key, _| !matches(key));
+    }
+}
+
+/// `CacheStore` keeping one JSON file per response in a directory, which keeps responses out of memory and
+/// lets several caches share them
+///
+/// Responses are written in plaintext, so the directory holds grades and other personal data of the student.
+/// On unix the directory is created readable by its owner only and so is every file written to it. Keys hold
+/// the password as a `credential_hash`, whose secret is not written, so entries of an earlier process are
+/// never served and are replaced by the first response of the same call.
+///
+/// Entries are stored as `svcache-<hash>.json`, other files in the directory are never touched. Files are read
+/// and written synchronously, failing writes are ignored and unreadable files are treated as missing entries.
+#[derive(Debug, Clone)]
+pub struct DiskStore {
+    dir: PathBuf,
+}
+
+/// Prefix of the files written by `DiskStore`, followed by the hash of the key
+const FILE_PREFIX: &str = "svcache-";
+
+#[derive(Serialize, Deserialize)]
+struct DiskEntry {
+    key: CacheKey,
+    entry: CacheEntry,
+}
+
+impl DiskStore {
+    /// Uses `dir` as the cache directory, creating it if needed
+    pub fn new(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        let mut builder = fs::DirBuilder::new();
+        builder.recursive(true);
+        #[cfg(unix)]
+        std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+        builder.create(&dir)?;
+
+        Ok(DiskStore { dir })
+    }
+
+    fn path(&self, key: &CacheKey) -> PathBuf {
+        // FNV-1a, stable across runs and compiler versions unlike the std hasher. The credential is left out so
+        // a call made by a later process overwrites the entry of an earlier one instead of adding a file
+        let hash = [&key.uri, &key.user, &key.handle, &key.method, &key.params]
+            .iter()
+            .flat_map(|part| part.bytes().chain(Some(0)))
+            .fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3));
+
+        self.dir.join(format!("{}{:016x}.json", FILE_PREFIX, hash))
+    }
+
+    fn is_cache_file(path: &std::path::Path) -> bool {
+        path.file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_prefix(FILE_PREFIX)?.strip_suffix(".json"))
+            .is_some_and(|hash| hash.len() == 16 && hash.bytes().all(|b| b.is_ascii_hexdigit()))
+    }
+
+    fn read(path: &std::path::Path) -> Option<DiskEntry> {
+        serde_json::from_slice(&fs::read(path).ok()?).ok()
+    }
+
+    fn write(path: &std::path::Path, json: &[u8]) -> io::Result<()> {
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+        options.open(path)?.write_all(json)
+    }
+}
+
+impl CacheStore for DiskStore {
+    fn get(&self, key: &CacheKey) -> Option<CacheEntry> {
+        Self::read(&self.path(key))
+            .filter(|disk| disk.key == *key)
+            .map(|disk| disk.entry)
+    }
+
+    fn put(&self, key: &CacheKey, entry: CacheEntry) {
+        if let Ok(json) = serde_json::to_vec(&DiskEntry { key: key.clone(), entry }) {
+            let _ = Self::write(&self.path(key), &json);
+        }
+    }
+
+    fn remove(&self, key: &CacheKey) {
+        let path = self.path(key);
+        if Self::read(&path).is_some_and(|disk| disk.key == *key) {
+            let _ = fs::remove_file(path);
+        }
+    }
+
+    fn remove_where(&self, matches: &dyn Fn(&CacheKey) -> bool) {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+
+        for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+            // files which are not cache entries are left alone, the directory may be shared
+            let remove = Self::is_cache_file(&path)
+                && Self::read(&path).is_some_and(|disk| matches(&disk.key));
+
+            if remove {
+                let _ = fs::remove_file(path);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(method: &str) -> CacheKey {
+        CacheKey {
+            uri: String::from("https://sv.test"),
+            user: String::from("student"),
+            credential: credential_hash("student", "pwd"),
+            handle: String::from("PXPWebServices"),
+            method: method.to_owned(),
+            params: String::from("<Parms>\n</Parms>"),
+        }
+    }
+
+    #[test]
+    fn expiry_and_invalidation() {
+        let cache = Cache::new(MemoryStore::new(), CachePolicy::default());
+        assert_eq!(cache.ttl_of("Gradebook"), Some(Duration::from_secs(300)));
+        assert_eq!(cache.ttl_of("UpdateDeviceToken"), None);
+
+        cache.put(&key("Gradebook"), String::from("<Gradebook />"));
+        cache.put(&key("StudentInfo"), String::from("<StudentInfo />"));
+        assert_eq!(cache.get(&key("Gradebook"), Duration::from_secs(300)).as_deref(), Some("<Gradebook />"));
+        cache.put(&key("Attendance"), String::from("<Attendance />"));
+        assert_eq!(cache.get(&key("Attendance"), Duration::from_secs(0)), None);
+        assert_eq!(cache.store.get(&key("Attendance")), None, "expired entries are removed");
+
+        cache.invalidate_method(Method::GradeBook);
+        assert_eq!(cache.get(&key("Gradebook"), Duration::from_secs(300)), None);
+        assert!(cache.get(&key("StudentInfo"), Duration::from_secs(300)).is_some());
+    }
+
+    #[test]
+    fn keyed_credentials() {
+        assert_eq!(credential_hash("student", "pwd"), credential_hash("student", "pwd"));
+        assert_ne!(credential_hash("student", "pwd"), credential_hash("student", "pwd2"));
+
+        // not the unkeyed digest, which could be checked against guessed passwords
+        use sha2::Digest;
+        let plain = Sha256::digest(b"student\0pwd").iter().map(|b| format!("{:02x}", b)).collect::<String>();
+        assert_ne!(credential_hash("student", "pwd"), plain);
+    }
+
+    #[test]
+    fn disk_store() {
+        let dir = std::env::temp_dir().join(format!("studentvue-cache-{}", std::process::id()));
+        let entry = CacheEntry { body: String::from("<Attendance />"), stored_at: SystemTime::now() };
+
+        let store = DiskStore::new(&dir).unwrap();
+        store.put(&key("Attendance"), entry.clone());
+        assert_eq!(DiskStore::new(&dir).unwrap().get(&key("Attendance")), Some(entry.clone()));
+        assert_eq!(store.get(&key("Gradebook")), None);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = |path: &std::path::Path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+            assert_eq!(mode(&dir), 0o700);
+            assert_eq!(mode(&store.path(&key("Attendance"))), 0o600);
+        }
+
+        // another password, or the same one hashed by another process, neither reads nor adds an entry
+        let other = CacheKey { credential: credential_hash("student", "other"), ..key("Attendance") };
+        assert_eq!(store.get(&other), None);
+        store.put(&other, entry.clone());
+        assert_eq!(store.get(&key("Attendance")), None);
+        store.put(&key("Attendance"), entry);
+
+        // unrelated files sharing the directory survive invalidation
+        fs::write(dir.join("notes.json"), "{}").unwrap();
+        fs::write(dir.join("svcache-corrupt.json"), "not json").unwrap();
+        Cache::new(store.clone(), CachePolicy::default()).clear();
+        assert_eq!(store.get(&key("Attendance")), None);
+        assert!(dir.join("notes.json").exists() && dir.join("svcache-corrupt.json").exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -4,29 +4,86 @@
 //! `Client` which can seamlessly interface with any school's studentvue API
 
 use crate::{
-    request::WebHandle,
+    cache::{self, Cache, CacheKey},
+    limit::{self, RateLimiter},
+    request::{self, Transport, WebHandle},
+    retry::RetryPolicy,
     enums::*,
     model::*,
     error::VueError,
+    xml,
 };
 use std::{
-    borrow::Cow,
     fmt,
     fmt::Write,
+    sync::Arc,
 };
+use futures_util::{StreamExt, TryStreamExt, stream};
 use quick_xml::de;
 use serde::Deserialize;
+use zeroize::Zeroizing;
 
-// POST request endpoint (ProcessWebServiceRequest is redundant for SOAP requests)
-// In this case since we are solely making regular POST requests it is required
-static ENDPOINT: &str = "/Service/PXPCommunication.asmx/ProcessWebServiceRequest";
+// Edupoint's public district lookup service along with the shared credentials used by the official app
+static DISTRICT_LOOKUP_URL: &str = "https://support.edupoint.com";
+static DISTRICT_LOOKUP_USER: &str = "EdupointDistrictInfo";
+static DISTRICT_LOOKUP_PWD: &str = "Edup01nt";
 
 /// Struct which connects to the StudentVUE service
-#[derive(Debug, Clone, PartialEq)]
-pub struct Client<'c> {
-    pub uri: Cow<'c, str>,
-    pub user: &'c str,
-    pub pwd: &'c str,
+///
+/// `Client` owns its credentials and is cheap to clone, so it can be shared across tasks or stored in
+/// long-lived state. Requests are sent through a `Transport`, which defaults to the reqwest based `WebHandle`
+pub struct Client<T: Transport = WebHandle> {
+    /// Base url of the district, the service endpoint is appended per `WebServiceHandle`
+    pub uri: Arc<str>,
+    credentials: Arc<Credentials>,
+    transport: Arc<T>,
+    cache: Option<Arc<Cache>>,
+    retry: Arc<RetryPolicy>,
+    limiter: Option<RateLimiter>,
+}
+
+/// Account credentials, the password is zeroized once the last `Client` referencing it is dropped
+#[derive(PartialEq)]
+struct Credentials {
+    user: String,
+    pwd: Zeroizing<String>,
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("user", &self.user)
+            .field("pwd", &"<redacted>")
+            .finish()
+    }
+}
+
+impl<T: Transport> Clone for Client<T> {
+    fn clone(&self) -> Self {
+        Client {
+            uri: self.uri.clone(),
+            credentials: self.credentials.clone(),
+            transport: self.transport.clone(),
+            cache: self.cache.clone(),
+            retry: self.retry.clone(),
+            limiter: self.limiter.clone(),
+        }
+    }
+}
+
+impl<T: Transport> fmt::Debug for Client<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Client")
+            .field("uri", &self.uri)
+            .field("credentials", &self.credentials)
+            .finish()
+    }
+}
+
+impl<T: Transport> PartialEq for Client<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.uri == other.uri && self.credentials == other.credentials
+    }
 }
 
 /// StudentVUE parameter builder
@@ -35,24 +92,86 @@ pub struct ParamBuilder {
     pub param_str: String,
 }
 
-impl<'c> Client<'c> {
+impl Client {
     /// Instantiates a new `Client` with the username, password, and corresponding StudentVUE district url
-    pub fn create(district_url: &'c str, username: &'c str, password: &'c str) -> Self {
+    pub fn create(district_url: &str, username: &str, password: &str) -> Self {
+        Client::create_with_transport(district_url, username, password, WebHandle::new())
+    }
+
+    /// Instantiates a `Client` for Edupoint's district lookup service, which only accepts `HDInfoServices` methods
+    pub fn district_lookup() -> Client {
+        Client::create(DISTRICT_LOOKUP_URL, DISTRICT_LOOKUP_USER, DISTRICT_LOOKUP_PWD)
+    }
+}
+
+impl<T: Transport> Client<T> {
+    /// Instantiates a new `Client` which sends its requests through the given `Transport`
+    pub fn create_with_transport(district_url: &str, username: &str, password: &str, transport: T) -> Self {
         Client {
-            uri: [district_url, ENDPOINT].concat().into(),
-            user: username,
-            pwd: password,
+            uri: district_url.trim_end_matches('/').into(),
+            credentials: Arc::new(Credentials {
+                user: username.to_owned(),
+                pwd: Zeroizing::new(password.to_owned()),
+            }),
+            transport: Arc::new(transport),
+            cache: None,
+            retry: Arc::new(RetryPolicy::never()),
+            limiter: None,
         }
     }
 
-    /// Calls a method from a specified `WebServiceHandle` with the specified parameters
+    /// Sends every request, including retries, through `limiter`; clients sharing a limiter share its limits
+    pub fn with_rate_limiter(mut self, limiter: RateLimiter) -> Self {
+        self.limiter = Some(limiter);
+        self
+    }
+
+    /// Resends service calls failing with transient errors according to `policy`, by default calls are not retried
+    pub fn with_retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Arc::new(policy);
+        self
+    }
+
+    /// Serves service calls from `cache` while their responses are fresh, the cache is shared with clones
+    /// of the `Client`
+    pub fn with_cache(mut self, cache: Cache) -> Self {
+        self.cache = Some(Arc::new(cache));
+        self
+    }
+
+    /// The response cache, if any, used to invalidate cached responses
+    #[inline]
+    pub fn cache(&self) -> Option<&Cache> {
+        self.cache.as_deref()
+    }
+
+    /// Drops this user's cached responses of `method`
+    pub fn invalidate(&self, method: Method) {
+        if let Some(cache) = &self.cache {
+            cache.invalidate(&self.uri, &self.credentials.user, method);
+        }
+    }
+
+    /// Username the `Client` authenticates with
+    #[inline]
+    pub fn user(&self) -> &str {
+        &self.credentials.user
+    }
+
+    /// The `Transport` requests are sent through
+    #[inline]
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    /// Calls a method with the specified parameters through the `WebServiceHandle` the method belongs to
     ///
     /// # Example
     ///
-    /// ```
+    /// ```no_run
     /// use studentvue::{
     ///     client::Client,
-    ///     enums::{Method, WebServiceHandle},
+    ///     enums::Method,
     ///     client::ParamBuilder
     /// };
     /// use std::env;
@@ -62,55 +181,195 @@ impl<'c> Client<'c> {
     ///     let (user, pwd) = (env::args().next().unwrap(), env::args().next().unwrap());
     ///
     ///     let client = Client::create("https://studentvue.phoenixunion.org", user.as_str(), pwd.as_str());
-    ///     let xml = client.call_service(WebServiceHandle::PXPWebServices, Method::StudentSchoolInfo, ParamBuilder::create())
+    ///     let xml = client.call_service(Method::StudentSchoolInfo, ParamBuilder::create())
     ///         .await
     ///         .expect("Could not call service!");
     ///
     ///     println!("{}", xml);
     /// }
     /// ```
-    pub async fn call_service(
+    #[inline]
+    pub async fn call_service(&self, method_name: Method, param_str: ParamBuilder) -> Result<String, VueError> {
+        self.call_service_with_handle(method_name.web_service_handle(), method_name, param_str)
+            .await
+    }
+
+    /// Calls a method from a specified `WebServiceHandle` with the specified parameters, overriding the
+    /// handle the method would normally be sent through
+    pub async fn call_service_with_handle(
         &self,
         web_service_handle: WebServiceHandle,
         method_name: Method,
         param_str: ParamBuilder,
     ) -> Result<String, VueError> {
+        self.call_raw(web_service_handle.into(), method_name.into(), param_str)
+            .await
+    }
+
+    /// Calls a method like `call_service`, parsing the response into a generic XML tree for methods without a
+    /// typed model
+    #[inline]
+    pub async fn call_service_tree(&self, method_name: Method, param_str: ParamBuilder) -> Result<xml::Element, VueError> {
+        let xml_data = self.call_service(method_name, param_str)
+            .await?;
+
+        xml::Element::parse(&xml_data)
+    }
+
+    /// Calls a method by name through a web service handle given by name, allowing methods which are missing
+    /// from `Method` to be called
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use studentvue::client::{Client, ParamBuilder};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::create("https://studentvue.phoenixunion.org", "user", "pwd");
+    ///     let parms = ParamBuilder::create()
+    ///         .add_param("ChildIntID", "0")
+    ///         .unwrap();
+    ///     let xml = client.call_raw("PXPWebServices", "StudentHealthInfo", parms)
+    ///         .await
+    ///         .expect("Could not call service!");
+    ///
+    ///     println!("{}", xml);
+    /// }
+    /// ```
+    pub async fn call_raw(&self, web_service_handle: &str, method_name: &str, param_str: ParamBuilder) -> Result<String, VueError> {
+        let params = param_str.build_string();
+        let cached = self.cache.as_ref().and_then(|cache| {
+            let ttl = cache.ttl_of(method_name)?;
+            let key = CacheKey {
+                uri: self.uri.to_string(),
+                user: self.credentials.user.clone(),
+                credential: cache::credential_hash(&self.credentials.user, &self.credentials.pwd),
+                handle: web_service_handle.to_owned(),
+                method: method_name.to_owned(),
+                params: params.clone(),
+            };
+
+            Some((cache, key, ttl))
+        });
+
+        if let Some((cache, key, ttl)) = &cached {
+            if let Some(body) = cache.get(key, *ttl) {
+                return Ok(body);
+            }
+        }
+
         let body = [
-            ("userID", self.user),
-            ("password", self.pwd),
+            ("userID", self.credentials.user.as_str()),
+            ("password", self.credentials.pwd.as_str()),
             ("skipLoginLog", "true"),
             ("parent", "false"),
-            ("webServiceHandleName", web_service_handle.into()),
-            ("methodName", method_name.into()),
-            ("paramStr", &param_str.build_string())
+            ("webServiceHandleName", web_service_handle),
+            ("methodName", method_name),
+            ("paramStr", &params)
         ];
 
-        Ok(
-            WebHandle::send(&self.uri, body)
-                .await?
-        )
+        let uri = [self.uri.as_ref(), WebServiceHandle::endpoint_of(web_service_handle)].concat();
+        let mut attempt = 1;
+        let response = loop {
+            let permit = match &self.limiter {
+                Some(limiter) => Some(limiter.acquire(&limit::host_of(&self.uri)).await),
+                None => None,
+            };
+            let result = self.transport.post_form(&uri, &body).await.map(|body| request::unescape(&body));
+            drop(permit);
+
+            let err = match result {
+                Ok(response) => match service_error(&response) {
+                    Some(err) => err,
+                    None => break response,
+                },
+                Err(err) => err,
+            };
+
+            if !(self.retry.retries_method(method_name) && self.retry.is_retryable(&err)) {
+                return Err(err);
+            }
+            if attempt >= self.retry.max_attempts() {
+                return Err(match attempt {
+                    1 => err,
+                    attempts => VueError::RetriesExhausted { attempts, last: Box::new(err) },
+                });
+            }
+
+            tokio::time::sleep(self.retry.backoff(attempt)).await;
+            attempt += 1;
+        };
+
+        if let Some((cache, key, _)) = cached {
+            cache.put(&key, response.clone());
+        }
+
+        Ok(response)
+    }
+
+    /// Calls a method by name like `call_raw`, parsing the response into a generic XML tree
+    #[inline]
+    pub async fn call_raw_tree(&self, web_service_handle: &str, method_name: &str, param_str: ParamBuilder) -> Result<xml::Element, VueError> {
+        let xml_data = self.call_raw(web_service_handle, method_name, param_str)
+            .await?;
+
+        xml::Element::parse(&xml_data)
     }
 
     /// Retrieves grades from a student; can be current or from a specified reporting period
     #[inline]
     pub async fn get_grades(&self, report_period: Option<u64>) -> Result<grade::GbData, VueError> {
-        let parms = if report_period.is_none() {
+        let parms = if let Some(period) = report_period {
             ParamBuilder::create()
+                .add_elements(&[ParamType::ReportPeriod(period)])?
         } else {
             ParamBuilder::create()
-                .add_elements(&[ParamType::ReportPeriod(report_period.unwrap())])?
         };
 
-        let xml_data = self.call_service(WebServiceHandle::PXPWebServices, Method::GradeBook, parms)
+        let xml_data = self.call_service(Method::GradeBook, parms)
                 .await?;
 
         Ok(de::from_str(xml_data.as_str())?)
     }
 
+    /// Lists every reporting period the server advertises, across all terms
+    pub async fn get_reporting_periods(&self) -> Result<Vec<grade::ReportPeriod>, VueError> {
+        let parms = ParamBuilder::create()
+            .add_elements(&[ParamType::LoadAllTerms])?;
+
+        let xml_data = self.call_service(Method::GradeBook, parms)
+            .await?;
+        let grades: grade::GbData = de::from_str(xml_data.as_str())?;
+
+        Ok(grades.gradebook.reporting_periods.period)
+    }
+
+    /// Retrieves the gradebook of every advertised reporting period, requesting at most `max_concurrent`
+    /// periods at a time
+    pub async fn get_all_grades(&self, max_concurrent: usize) -> Result<grade::MultiPeriodGradebook, VueError> {
+        let mut periods = self.get_reporting_periods()
+            .await?;
+        periods.sort_by_key(|p| p.index);
+
+        let periods = stream::iter(periods)
+            .map(|period| async move {
+                let grades = self.get_grades(Some(period.index))
+                    .await?;
+
+                Ok::<_, VueError>(grade::PeriodGradebook { period, gradebook: grades.gradebook })
+            })
+            .buffered(max_concurrent.max(1))
+            .try_collect()
+            .await?;
+
+        Ok(grade::MultiPeriodGradebook { periods })
+    }
+
     /// Gets the absences from the student
     #[inline]
     pub async fn get_attendance(&self) -> Result<attendance::AttData, VueError> {
-        let xml_data = self.call_service(WebServiceHandle::PXPWebServices, Method::Attendance, ParamBuilder::create())
+        let xml_data = self.call_service(Method::Attendance, ParamBuilder::create())
             .await?;
 
         Ok(de::from_str(xml_data.as_str())?)
@@ -119,16 +378,25 @@ impl<'c> Client<'c> {
     /// Retrieves student information such as their name, address, and grade
     #[inline]
     pub async fn get_student_info(&self) -> Result<student::Student, VueError> {
-        let xml_data = self.call_service(WebServiceHandle::PXPWebServices, Method::StudentInfo, ParamBuilder::create())
+        let xml_data = self.call_service(Method::StudentInfo, ParamBuilder::create())
             .await?;
 
         Ok(de::from_str(xml_data.as_str())?)
     }
 
-    /// Retrieves the student's current school schedule
+    /// Retrieves the student's school schedule; can be the current term or a specified term index, optionally
+    /// for a concurrently attended school identified by its `ConcurrentSchOrgYearGU`
     #[inline]
-    pub async fn get_schedule(&self) -> Result<schedule::StudentClassSchedule, VueError> {
-        let xml_data = self.call_service(WebServiceHandle::PXPWebServices, Method::StudentClassList, ParamBuilder::create())
+    pub async fn get_schedule(&self, term_index: Option<u8>, concurrent_school: Option<&str>) -> Result<schedule::StudentClassSchedule, VueError> {
+        let mut parms = ParamBuilder::create();
+        if let Some(term) = term_index {
+            parms.add_elements(&[ParamType::TermIndex(term)])?;
+        }
+        if let Some(gu) = concurrent_school {
+            parms.add_elements(&[ParamType::ConcurrentSchOrgYearGU(gu)])?;
+        }
+
+        let xml_data = self.call_service(Method::StudentClassList, parms)
             .await?;
 
         Ok(de::from_str(xml_data.as_str())?)
@@ -137,13 +405,87 @@ impl<'c> Client<'c> {
     /// Grabs information about the currently attended school
     #[inline]
     pub async fn get_school_info(&self) -> Result<school::StudentSchoolInfoListing, VueError> {
-        let xml_data = self.call_service(WebServiceHandle::PXPWebServices, Method::StudentSchoolInfo, ParamBuilder::create())
+        let xml_data = self.call_service(Method::StudentSchoolInfo, ParamBuilder::create())
+            .await?;
+
+        Ok(de::from_str(xml_data.as_str())?)
+    }
+
+    /// Retrieves the messages sent to the student
+    #[inline]
+    pub async fn get_messages(&self) -> Result<message::PXPMessagesData, VueError> {
+        let xml_data = self.call_service(Method::GetPXPMessages, ParamBuilder::create())
+            .await?;
+
+        Ok(de::from_str(xml_data.as_str())?)
+    }
+
+    /// Retrieves the calendar of the month containing `date`, or the current month
+    #[inline]
+    pub async fn get_calendar(&self, date: Option<date::Date>) -> Result<calendar::StudentCalendar, VueError> {
+        let parms = if let Some(date) = date {
+            ParamBuilder::create()
+                .add_elements(&[ParamType::RequestDate(date)])?
+        } else {
+            ParamBuilder::create()
+        };
+
+        let xml_data = self.call_service(Method::StudentCalendar, parms)
+            .await?;
+
+        Ok(de::from_str(xml_data.as_str())?)
+    }
+
+    /// Registers a push notification device token so the district can notify the device
+    #[inline]
+    pub async fn register_device_token(&self, token: &str, platform: Platform) -> Result<device::DeviceTokenResponse, VueError> {
+        self.update_device_token(token, platform, true).await
+    }
+
+    /// Removes a previously registered push notification device token
+    #[inline]
+    pub async fn unregister_device_token(&self, token: &str, platform: Platform) -> Result<device::DeviceTokenResponse, VueError> {
+        self.update_device_token(token, platform, false).await
+    }
+
+    async fn update_device_token(&self, token: &str, platform: Platform, register: bool) -> Result<device::DeviceTokenResponse, VueError> {
+        let parms = ParamBuilder::create()
+            .add_elements(&[ParamType::DeviceToken(token),
+                ParamType::DeviceType(platform),
+                ParamType::RegisterDevice(register)
+            ])?;
+
+        let xml_data = self.call_service(Method::UpdateDeviceToken, parms)
+            .await?;
+
+        Ok(de::from_str(xml_data.as_str())?)
+    }
+
+    /// Looks up the districts matching a zip code, this must be called on `Client::district_lookup`
+    #[inline]
+    pub async fn get_matching_districts(&self, zip_code: &str) -> Result<district::MatchingDistricts, VueError> {
+        let parms = ParamBuilder::create()
+            .add_elements(&[ParamType::Key, ParamType::MatchToDistrictZipCode(zip_code)])?;
+
+        let xml_data = self.call_service(Method::GetMatchingDistrictList, parms)
             .await?;
 
         Ok(de::from_str(xml_data.as_str())?)
     }
 }
 
+/// Error carried by an `RT_ERROR` response, which the service answers with a 200 status
+fn service_error(response: &str) -> Option<VueError> {
+    if !response.contains("<RT_ERROR") {
+        return None;
+    }
+
+    let root = xml::Element::parse(response).ok()?;
+    let error = if root.name == "RT_ERROR" { &root } else { root.child("RT_ERROR")? };
+
+    Some(VueError::from_service_message(error.attribute("ERROR_MESSAGE").unwrap_or_default()))
+}
+
 impl ParamBuilder {
     /// Creates a new `ParamBuilder` instance
     pub fn create() -> Self {
@@ -156,8 +498,20 @@ impl ParamBuilder {
     #[inline]
     pub fn add_elements(&mut self, params: &[ParamType]) -> Result<Self, std::fmt::Error> {
         for p in params.iter() {
-            write!(&mut self.param_str, "{}\n", p.to_string())?;
+            writeln!(&mut self.param_str, "{}", p)?;
+        }
+
+        Ok(self.clone())
+    }
+
+    /// Adds an element with an arbitrary name, the value is escaped while a name which is not a valid XML
+    /// element name is rejected
+    #[inline]
+    pub fn add_param(&mut self, name: &str, value: &str) -> Result<Self, VueError> {
+        if !xml::is_name(name) {
+            return Err(VueError::InvalidName(name.to_owned()));
         }
+        writeln!(&mut self.param_str, "<{0}>{1}</{0}>", name, xml::escape(value))?;
 
         Ok(self.clone())
     }
@@ -172,18 +526,322 @@ impl ParamBuilder {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{
+        cache::{CachePolicy, MemoryStore},
+        model::date::Date,
+        request::{LocalTransportFuture, TransportFuture},
+    };
+    use std::{cell::Cell, rc::Rc, sync::Mutex, time::Duration};
+
+    type Request = (String, Vec<(String, String)>);
+
+    // Records every request and answers with a fixed body
+    #[derive(Default)]
+    struct Recorder {
+        requests: Mutex<Vec<Request>>,
+    }
+
+    impl Transport for Recorder {
+        type Future<'a> = TransportFuture<'a>;
+
+        fn post_form<'a>(&'a self, uri: &'a str, params: &'a [(&'a str, &'a str)]) -> TransportFuture<'a> {
+            let params = params.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+            self.requests.lock().unwrap().push((uri.to_owned(), params));
+
+            Box::pin(async { Ok(String::from("<string><RT_RESPONSE RESPONSE_STATUS=\"Success\" /></string>")) })
+        }
+    }
+
+    #[tokio::test]
+    async fn custom_transport() {
+        let client = Client::create_with_transport("https://sv.test/", "student", "pwd", Recorder::default());
+        let res = client.register_device_token("f3a9c1", Platform::Android)
+            .await
+            .unwrap();
+        assert!(res.is_success());
+
+        client.call_service(Method::GetMatchingDistrictList, ParamBuilder::create())
+            .await
+            .unwrap();
+
+        let requests = client.transport().requests.lock().unwrap();
+        assert_eq!(requests[0].0, "https://sv.test/Service/PXPCommunication.asmx/ProcessWebServiceRequest");
+        assert!(requests[0].1.contains(&("methodName".into(), "UpdateDeviceToken".into())));
+        assert_eq!(requests[1].0, "https://sv.test/Service/HDInfoCommunication.asmx/ProcessWebServiceRequest");
+        assert!(requests[1].1.contains(&("webServiceHandleName".into(), "HDInfoServices".into())));
+    }
+
+    // Answers gradebook requests with a single course graded by the requested period
+    struct GradebookServer;
+
+    impl Transport for GradebookServer {
+        type Future<'a> = TransportFuture<'a>;
+
+        fn post_form<'a>(&'a self, _uri: &'a str, params: &'a [(&'a str, &'a str)]) -> TransportFuture<'a> {
+            let parms = params.iter().find(|(k, _)| *k == "paramStr").unwrap().1;
+            let period: u32 = parms.split("<ReportPeriod>").nth(1)
+                .and_then(|p| p.split('<').next())
+                .map_or(0, |p| p.parse().unwrap());
+
+            let xml = format!("<string><Gradebook><ReportingPeriods>\
+                <ReportPeriod Index=\"1\" GradePeriod=\"Quarter 2\" StartDate=\"\" EndDate=\"\" />\
+                <ReportPeriod Index=\"0\" GradePeriod=\"Quarter 1\" StartDate=\"\" EndDate=\"\" />\
+                </ReportingPeriods><Courses><Course Period=\"1\" Title=\"Chemistry\">\
+                <Marks><Mark CalculatedScoreString=\"B\" CalculatedScoreRaw=\"{}\" /></Marks>\
+                </Course></Courses></Gradebook></string>", 80 + period * 5);
+
+            Box::pin(async move { Ok(xml) })
+        }
+    }
+
+    #[tokio::test]
+    async fn all_reporting_periods() {
+        let client = Client::create_with_transport("https://sv.test", "student", "pwd", GradebookServer);
+        let grades = client.get_all_grades(2)
+            .await
+            .unwrap();
+
+        let trend: Vec<_> = grades.course_trend("Chemistry")
+            .into_iter()
+            .map(|(period, percent)| (period.name.as_str(), percent))
+            .collect();
+        assert_eq!(trend, [("Quarter 1", Some(80.0)), ("Quarter 2", Some(85.0))]);
+    }
+
+    #[tokio::test]
+    async fn raw_call() {
+        let client = Client::create_with_transport("https://sv.test", "student", "pwd", Recorder::default());
+        let parms = ParamBuilder::create()
+            .add_param("SearchText", "Smith & <Jones>")
+            .unwrap();
+        let tree = client.call_raw_tree("HDInfoServices", "GetNewMethod", parms)
+            .await
+            .unwrap();
+        assert_eq!(tree.child("RT_RESPONSE").unwrap().attribute("RESPONSE_STATUS"), Some("Success"));
+
+        let requests = client.transport().requests.lock().unwrap();
+        assert_eq!(requests[0].0, "https://sv.test/Service/HDInfoCommunication.asmx/ProcessWebServiceRequest");
+        assert!(requests[0].1.contains(&("methodName".into(), "GetNewMethod".into())));
+        assert!(requests[0].1.contains(&("paramStr".into(), "<Parms>\n<SearchText>Smith &amp; &lt;Jones&gt;</SearchText>\n</Parms>".into())));
+    }
+
+    #[tokio::test]
+    async fn cached_calls() {
+        let client = Client::create_with_transport("https://sv.test", "student", "pwd", Recorder::default())
+            .with_cache(Cache::new(MemoryStore::new(), CachePolicy::default()));
+        let count = |client: &Client<Recorder>| client.transport().requests.lock().unwrap().len();
+        let call = |client: &Client<Recorder>, method, parms: &[ParamType]| {
+            let parms = ParamBuilder::create().add_elements(parms).unwrap();
+            let client = client.clone();
+
+            async move { client.call_service(method, parms).await.unwrap() }
+        };
+
+        call(&client, Method::StudentSchoolInfo, &[]).await;
+        call(&client, Method::StudentSchoolInfo, &[]).await;
+        assert_eq!(count(&client), 1);
+
+        // calls with other parameters or uncached methods always reach the server
+        call(&client, Method::GradeBook, &[]).await;
+        call(&client, Method::GradeBook, &[ParamType::ReportPeriod(1)]).await;
+        call(&client, Method::UpdateDeviceToken, &[]).await;
+        call(&client, Method::UpdateDeviceToken, &[]).await;
+        assert_eq!(count(&client), 5);
+
+        client.invalidate(Method::StudentSchoolInfo);
+        call(&client, Method::StudentSchoolInfo, &[]).await;
+        call(&client, Method::GradeBook, &[]).await;
+        assert_eq!(count(&client), 6);
+    }
+
+    // Fails with a connection reset until `failures` attempts were made, then rejects the credentials
+    struct Flaky {
+        failures: usize,
+        attempts: Mutex<usize>,
+    }
+
+    impl Transport for Flaky {
+        type Future<'a> = TransportFuture<'a>;
+
+        fn post_form<'a>(&'a self, _uri: &'a str, _params: &'a [(&'a str, &'a str)]) -> TransportFuture<'a> {
+            let mut attempts = self.attempts.lock().unwrap();
+            *attempts += 1;
+            let failed = *attempts <= self.failures;
+
+            Box::pin(async move {
+                if failed {
+                    Err(std::io::Error::from(std::io::ErrorKind::ConnectionReset).into())
+                } else {
+                    Ok(String::from("<string><RT_ERROR ERROR_MESSAGE=\"Invalid user id or password\" /></string>"))
+                }
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn retries() {
+        let policy = RetryPolicy::new(3).with_backoff(Duration::from_millis(1), Duration::from_millis(1));
+        let client = |failures| {
+            Client::create_with_transport("https://sv.test", "student", "pwd", Flaky { failures, attempts: Mutex::new(0) })
+                .with_retry(policy.clone())
+        };
+
+        // recovers from the resets, the credential error which follows is returned without retrying
+        let recovering = client(2);
+        let err = recovering.get_grades(None).await.unwrap_err();
+        assert!(matches!(err, VueError::Credentials(ref msg) if msg == "Invalid user id or password"));
+        assert_eq!(*recovering.transport().attempts.lock().unwrap(), 3);
+
+        let rejected = client(0);
+        assert!(matches!(rejected.get_grades(None).await.unwrap_err(), VueError::Credentials(_)));
+        assert_eq!(*rejected.transport().attempts.lock().unwrap(), 1);
+
+        // calls changing data on the server are sent once
+        let update = client(5);
+        let err = update.call_service(Method::UpdatePXPMessage, ParamBuilder::create()).await.unwrap_err();
+        assert!(matches!(err, VueError::Io(_)));
+        assert_eq!(*update.transport().attempts.lock().unwrap(), 1);
+
+        let failing = client(5);
+        match failing.get_grades(None).await.unwrap_err() {
+            VueError::RetriesExhausted { attempts, last } => {
+                assert_eq!(attempts, 3);
+                assert!(matches!(*last, VueError::Io(_)));
+            }
+            err => panic!("unexpected error: {}", err),
+        }
+    }
+
+    // Tracks the most requests in flight at once
+    #[derive(Default)]
+    struct Slow {
+        in_flight: Mutex<(usize, usize)>,
+    }
+
+    impl Transport for Slow {
+        type Future<'a> = TransportFuture<'a>;
+
+        fn post_form<'a>(&'a self, _uri: &'a str, _params: &'a [(&'a str, &'a str)]) -> TransportFuture<'a> {
+            Box::pin(async move {
+                {
+                    let mut in_flight = self.in_flight.lock().unwrap();
+                    in_flight.0 += 1;
+                    in_flight.1 = in_flight.1.max(in_flight.0);
+                }
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                self.in_flight.lock().unwrap().0 -= 1;
+
+                Ok(String::from("<string />"))
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn shared_concurrency_limit() {
+        let limiter = RateLimiter::new(1000.0, 2).with_burst(100);
+        let transport = Arc::new(Slow::default());
+        let clients: Vec<_> = ["student1", "student2", "student3"].iter()
+            .map(|user| {
+                Client::create_with_transport("https://sv.test", user, "pwd", transport.clone())
+                    .with_rate_limiter(limiter.clone())
+            })
+            .collect();
+
+        let calls = clients.iter()
+            .flat_map(|c| vec![c.clone(); 2])
+            .map(|c| async move { c.call_service(Method::StudentInfo, ParamBuilder::create()).await });
+        for res in futures_util::future::join_all(calls).await {
+            res.unwrap();
+        }
+
+        assert_eq!(*transport.in_flight.lock().unwrap(), (0, 2));
+    }
+
+    #[tokio::test]
+    async fn cache_isolation() {
+        let store = Arc::new(MemoryStore::new());
+        let client = |uri, pwd| {
+            Client::create_with_transport(uri, "student", pwd, Recorder::default())
+                .with_cache(Cache::new(store.clone(), CachePolicy::default()))
+        };
+        let fetch = |client: Client<Recorder>| async move {
+            client.call_service(Method::GradeBook, ParamBuilder::create()).await.unwrap();
+            client.transport().requests.lock().unwrap().len()
+        };
+
+        assert_eq!(fetch(client("https://district-a.test", "pwd")).await, 1);
+        assert_eq!(fetch(client("https://district-a.test", "pwd")).await, 0);
+        // the same user id in another district and a wrong password both reach the server
+        assert_eq!(fetch(client("https://district-b.test", "pwd")).await, 1);
+        assert_eq!(fetch(client("https://district-a.test", "wrong")).await, 1);
+    }
 
     #[test]
     fn xml_building() {
-        let mut params = ParamBuilder::create()
+        let params = ParamBuilder::create()
             .add_elements(&[ParamType::AssignmentID("e2qekn"),
                 ParamType::ChildIntID(1),
                 ParamType::LanguageCode(0),
-                ParamType::RequestDate("1/23/19"),
+                ParamType::RequestDate(Date::new(2019, 1, 23).unwrap()),
                 ParamType::HealthImmunizations(true)
             ]).unwrap();
 
-        let res = "<Parms>\n<AssignmentID>e2qekn</AssignmentID>\n<ChildIntID>1</ChildIntID>\n<LanguageCode>0</LanguageCode>\n<RequestDate>1/23/19</RequestDate>\n<HealthImmunizations>true</HealthImmunizations>\n</Parms>";
+        let res = "<Parms>\n<AssignmentID>e2qekn</AssignmentID>\n<ChildIntID>1</ChildIntID>\n<LanguageCode>0</LanguageCode>\n<RequestDate>1/23/2019</RequestDate>\n<HealthImmunizations>true</HealthImmunizations>\n</Parms>";
+        assert_eq!(&params.build_string(), res);
+
+        for name in ["", "1st", "Search Text", "a><b", "Name/"] {
+            assert!(matches!(ParamBuilder::create().add_param(name, ""), Err(VueError::InvalidName(_))), "{}", name);
+        }
+        assert!(ParamBuilder::create().add_param("_Name-1.x", "").is_ok());
+    }
+
+    // Transport bound to a single thread, answering with the escaped envelope the service sends
+    #[derive(Default)]
+    struct Local(Rc<Cell<usize>>);
+
+    impl Transport for Local {
+        type Future<'a> = LocalTransportFuture<'a>;
+
+        fn post_form<'a>(&'a self, _uri: &'a str, _params: &'a [(&'a str, &'a str)]) -> LocalTransportFuture<'a> {
+            self.0.set(self.0.get() + 1);
+
+            Box::pin(async { Ok(String::from("<string>&lt;RT_RESPONSE RESPONSE_STATUS=\"Success\" /&gt;</string>")) })
+        }
+    }
+
+    #[tokio::test]
+    async fn local_transport() {
+        let client = Client::create_with_transport("https://sv.test", "student", "pwd", Local::default());
+        let res = client.register_device_token("f3a9c1", Platform::Android)
+            .await
+            .unwrap();
+
+        assert!(res.is_success());
+        assert_eq!(client.transport().0.get(), 1);
+    }
+
+    #[test]
+    fn owned_client() {
+        fn assert_shareable<T: Send + Sync + 'static>(_: &T) {}
+
+        let client = Client::create("https://studentvue.phoenixunion.org/", "student", "hunter2");
+        assert_shareable(&client);
+
+        assert_eq!(client.clone(), client);
+        assert_eq!(client.user(), "student");
+        assert_eq!(client.uri.as_ref(), "https://studentvue.phoenixunion.org");
+        assert!(!format!("{:?}", client).contains("hunter2"));
+    }
+
+    #[test]
+    fn device_token_params() {
+        let params = ParamBuilder::create()
+            .add_elements(&[ParamType::DeviceToken("f3a9c1"),
+                ParamType::DeviceType(Platform::IOS),
+                ParamType::RegisterDevice(false)
+            ]).unwrap();
+
+        let res = "<Parms>\n<DeviceToken>f3a9c1</DeviceToken>\n<DeviceType>iOS</DeviceType>\n<RegisterDevice>false</RegisterDevice>\n</Parms>";
         assert_eq!(&params.build_string(), res);
     }
 }
@@ -0,0 +1,407 @@
+//! Change detection between model snapshots
+//!
+//! This module provides the `Diff` trait which compares an older snapshot of a model with a newer one and
+//! reports the differences as typed events, e.g. to notify "New grade posted in Chemistry: 18/20".
+
+use crate::model::{
+    attendance::{AttData, Absence},
+    calendar::{Event, StudentCalendar},
+    date::Date,
+    message::{Message, PXPMessagesData},
+    grade::{Assignment, Course, GbData, Gradebook},
+    schedule::{Class, StudentClassSchedule},
+};
+use serde::Serialize;
+use std::fmt;
+
+/// Models which can be compared with a newer snapshot of themselves
+pub trait Diff {
+    type Event;
+
+    /// Events describing how `newer` differs from `self`
+    fn diff(&self, newer: &Self) -> Vec<Self::Event>;
+}
+
+/// Change in a gradebook
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum GradeEvent {
+    CourseAdded { class_name: String },
+    CourseDropped { class_name: String },
+    AssignmentAdded { class_name: String, assignment: Assignment },
+    AssignmentRemoved { class_name: String, assignment: Assignment },
+    /// Points of an assignment changed, including an ungraded assignment being graded
+    ScoreChanged { class_name: String, assignment: Assignment, old_points: String },
+    MarkChanged {
+        class_name: String,
+        old_mark: String,
+        new_mark: String,
+        old_percent: Option<f32>,
+        new_percent: Option<f32>,
+    },
+}
+
+/// Change in attendance
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AttendanceEvent {
+    AbsenceAdded { absence: Absence },
+    AbsenceRemoved { absence: Absence },
+    AbsenceChanged { old: Absence, new: Absence },
+}
+
+/// Change in the student's messages
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MessageEvent {
+    MessageReceived { message: Message },
+}
+
+/// Change in the student's calendar
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CalendarEvent {
+    EventAdded { event: Event },
+    EventRemoved { event: Event },
+}
+
+/// Change in a class schedule
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ScheduleEvent {
+    ClassAdded { class: Class },
+    ClassDropped { class: Class },
+    ClassChanged { old: Class, new: Class },
+}
+
+impl Diff for GbData {
+    type Event = GradeEvent;
+
+    fn diff(&self, newer: &Self) -> Vec<GradeEvent> {
+        self.gradebook.diff(&newer.gradebook)
+    }
+}
+
+impl Diff for Gradebook {
+    type Event = GradeEvent;
+
+    fn diff(&self, newer: &Self) -> Vec<GradeEvent> {
+        let (old, new) = (&self.courses.course, &newer.courses.course);
+        let mut events = Vec::new();
+
+        for course in new.iter() {
+            match old.iter().find(|c| same_course(c, course)) {
+                Some(previous) => diff_course(previous, course, &mut events),
+                None => {
+                    events.push(GradeEvent::CourseAdded { class_name: course.class_name.clone() });
+                    events.extend(assignments(course).map(|a| {
+                        GradeEvent::AssignmentAdded { class_name: course.class_name.clone(), assignment: a.clone() }
+                    }));
+                }
+            }
+        }
+
+        events.extend(old.iter()
+            .filter(|c| !new.iter().any(|n| same_course(c, n)))
+            .map(|c| GradeEvent::CourseDropped { class_name: c.class_name.clone() }));
+
+        events
+    }
+}
+
+fn assignments(course: &Course) -> impl Iterator<Item = &Assignment> {
+    course.current_mark().into_iter().flat_map(|m| m.assignment_list())
+}
+
+// A student may take two sections of a course, e.g. a lab in another period, so the period is part of its identity
+fn same_course(a: &Course, b: &Course) -> bool {
+    a.period == b.period && a.class_name == b.class_name
+}
+
+fn same_assignment(a: &Assignment, b: &Assignment) -> bool {
+    if a.id.is_empty() || b.id.is_empty() {
+        a.name == b.name
+    } else {
+        a.id == b.id
+    }
+}
+
+fn diff_course(old: &Course, new: &Course, events: &mut Vec<GradeEvent>) {
+    let class_name = || new.class_name.clone();
+
+    for assignment in assignments(new) {
+        match assignments(old).find(|a| same_assignment(a, assignment)) {
+            Some(previous) if previous.points != assignment.points => events.push(GradeEvent::ScoreChanged {
+                class_name: class_name(),
+                assignment: assignment.clone(),
+                old_points: previous.points.clone(),
+            }),
+            Some(_) => {}
+            None => events.push(GradeEvent::AssignmentAdded { class_name: class_name(), assignment: assignment.clone() }),
+        }
+    }
+
+    events.extend(assignments(old)
+        .filter(|a| !assignments(new).any(|n| same_assignment(a, n)))
+        .map(|a| GradeEvent::AssignmentRemoved { class_name: class_name(), assignment: a.clone() }));
+
+    let (old_mark, new_mark) = (old.current_mark(), new.current_mark());
+    let grade = |m: Option<&crate::model::grade::Mark>| m.map(|m| m.grade.clone()).unwrap_or_default();
+    let percent = |m: Option<&crate::model::grade::Mark>| m.and_then(|m| m.percent);
+
+    if grade(old_mark) != grade(new_mark) || percent(old_mark) != percent(new_mark) {
+        events.push(GradeEvent::MarkChanged {
+            class_name: class_name(),
+            old_mark: grade(old_mark),
+            new_mark: grade(new_mark),
+            old_percent: percent(old_mark),
+            new_percent: percent(new_mark),
+        });
+    }
+}
+
+impl Diff for AttData {
+    type Event = AttendanceEvent;
+
+    fn diff(&self, newer: &Self) -> Vec<AttendanceEvent> {
+        let (old, new) = (&self.attendance.absences.absence, &newer.attendance.absences.absence);
+        let find = |list: &'_ [Absence], date: Date| list.iter().find(|a| a.absence_date == date).cloned();
+        let mut events = Vec::new();
+
+        for absence in new.iter() {
+            match find(old, absence.absence_date) {
+                Some(previous) if previous != *absence => {
+                    events.push(AttendanceEvent::AbsenceChanged { old: previous, new: absence.clone() })
+                }
+                Some(_) => {}
+                None => events.push(AttendanceEvent::AbsenceAdded { absence: absence.clone() }),
+            }
+        }
+
+        events.extend(old.iter()
+            .filter(|a| find(new, a.absence_date).is_none())
+            .map(|a| AttendanceEvent::AbsenceRemoved { absence: a.clone() }));
+
+        events
+    }
+}
+
+impl Diff for PXPMessagesData {
+    type Event = MessageEvent;
+
+    fn diff(&self, newer: &Self) -> Vec<MessageEvent> {
+        let old = &self.messages.message_listings.message;
+
+        newer.messages.message_listings.message
+            .iter()
+            .filter(|m| !old.iter().any(|o| o.id == m.id))
+            .map(|m| MessageEvent::MessageReceived { message: m.clone() })
+            .collect()
+    }
+}
+
+impl Diff for StudentCalendar {
+    type Event = CalendarEvent;
+
+    fn diff(&self, newer: &Self) -> Vec<CalendarEvent> {
+        let (old, new) = (&self.calendar.event_lists.event, &newer.calendar.event_lists.event);
+        let same = |a: &Event, b: &Event| a.date == b.date && a.title == b.title && a.assignment_gu == b.assignment_gu;
+
+        let added = new.iter()
+            .filter(|e| !old.iter().any(|o| same(o, e)))
+            .map(|e| CalendarEvent::EventAdded { event: e.clone() });
+        let removed = old.iter()
+            .filter(|e| !new.iter().any(|n| same(n, e)))
+            .map(|e| CalendarEvent::EventRemoved { event: e.clone() });
+
+        added.chain(removed).collect()
+    }
+}
+
+impl Diff for StudentClassSchedule {
+    type Event = ScheduleEvent;
+
+    fn diff(&self, newer: &Self) -> Vec<ScheduleEvent> {
+        let (old, new) = (&self.schedule.class_list.class, &newer.schedule.class_list.class);
+        let find = |list: &'_ [Class], class: &Class| {
+            list.iter()
+                .find(|c| c.period == class.period && c.course_title == class.course_title)
+                .cloned()
+        };
+        let mut events = Vec::new();
+
+        for class in new.iter() {
+            match find(old, class) {
+                Some(previous) if previous != *class => {
+                    events.push(ScheduleEvent::ClassChanged { old: previous, new: class.clone() })
+                }
+                Some(_) => {}
+                None => events.push(ScheduleEvent::ClassAdded { class: class.clone() }),
+            }
+        }
+
+        events.extend(old.iter()
+            .filter(|c| find(new, c).is_none())
+            .map(|c| ScheduleEvent::ClassDropped { class: c.clone() }));
+
+        events
+    }
+}
+
+impl fmt::Display for GradeEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GradeEvent::CourseAdded { class_name } => write!(f, "Added to {}", class_name),
+            GradeEvent::CourseDropped { class_name } => write!(f, "Dropped from {}", class_name),
+            GradeEvent::AssignmentAdded { class_name, assignment } => match score(assignment) {
+                Some(score) => write!(f, "New grade posted in {}: {} {}", class_name, assignment.name, score),
+                None => write!(f, "New assignment in {}: {}", class_name, assignment.name),
+            },
+            GradeEvent::AssignmentRemoved { class_name, assignment } => {
+                write!(f, "Assignment removed from {}: {}", class_name, assignment.name)
+            }
+            GradeEvent::ScoreChanged { class_name, assignment, .. } => match score(assignment) {
+                Some(score) => write!(f, "Grade updated in {}: {} {}", class_name, assignment.name, score),
+                None => write!(f, "Grade removed in {}: {}", class_name, assignment.name),
+            },
+            GradeEvent::MarkChanged { class_name, old_mark, new_mark, new_percent, .. } => {
+                write!(f, "{} mark changed from {} to {}", class_name, old_mark, new_mark)?;
+                match new_percent {
+                    Some(percent) => write!(f, " ({:.1}%)", percent),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+fn score(assignment: &Assignment) -> Option<String> {
+    Some(format!("{}/{}", assignment.points_earned()?, assignment.points_possible()?))
+}
+
+impl fmt::Display for AttendanceEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AttendanceEvent::AbsenceAdded { absence } => write!(f, "New absence on {}: {}", absence.absence_date, absence.reason),
+            AttendanceEvent::AbsenceRemoved { absence } => write!(f, "Absence removed on {}", absence.absence_date),
+            AttendanceEvent::AbsenceChanged { new, .. } => write!(f, "Absence updated on {}: {}", new.absence_date, new.reason),
+        }
+    }
+}
+
+impl fmt::Display for MessageEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MessageEvent::MessageReceived { message } => write!(f, "New message from {}: {}", message.from, message.subject),
+        }
+    }
+}
+
+impl fmt::Display for CalendarEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CalendarEvent::EventAdded { event } => write!(f, "Added to the calendar on {}: {}", event.date, event.title),
+            CalendarEvent::EventRemoved { event } => write!(f, "Removed from the calendar on {}: {}", event.date, event.title),
+        }
+    }
+}
+
+impl fmt::Display for ScheduleEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScheduleEvent::ClassAdded { class } => write!(f, "Added {} in period {}", class.course_title, class.period),
+            ScheduleEvent::ClassDropped { class } => write!(f, "Dropped {} in period {}", class.course_title, class.period),
+            ScheduleEvent::ClassChanged { new, .. } => {
+                write!(f, "{} in period {} now meets in {} with {}", new.course_title, new.period, new.room_name, new.teacher)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quick_xml::de;
+
+    fn gradebook() -> GbData {
+        de::from_str(include_str!("../tests/fixtures/gradebook_assignments.xml")).unwrap()
+    }
+
+    #[test]
+    fn unchanged() {
+        assert!(gradebook().diff(&gradebook()).is_empty());
+    }
+
+    #[test]
+    fn grade_changes() {
+        let old = gradebook();
+        let mut new = gradebook();
+        let courses = &mut new.gradebook.courses.course;
+
+        let chem = &mut courses[0].marks[0].mark[0];
+        chem.assignments.assignment[2].points = String::from("18.00 / 20.0000");
+        chem.grade = String::from("B+");
+        chem.percent = Some(88.5);
+        courses.remove(3);
+
+        let events = old.diff(&new);
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].to_string(), "Grade updated in AP Chemistry: Final Exam 18/20");
+        assert!(matches!(&events[0], GradeEvent::ScoreChanged { old_points, .. } if old_points == "100.0000 Points Possible"));
+        assert_eq!(events[1].to_string(), "AP Chemistry mark changed from B to B+ (88.5%)");
+        assert_eq!(events[2], GradeEvent::CourseDropped { class_name: String::from("Teacher Aide") });
+    }
+
+    #[test]
+    fn course_changes() {
+        let mut old = gradebook();
+        let chemistry = old.gradebook.courses.course.remove(0);
+        let mut new = gradebook();
+        // the same course in another period is a different section
+        new.gradebook.courses.course[3].period = 5;
+
+        let events = old.diff(&new);
+        assert_eq!(events[0], GradeEvent::CourseAdded { class_name: chemistry.class_name.clone() });
+        // ungraded assignments of a new course are reported like those of existing courses
+        assert_eq!(events[1..4].iter().map(ToString::to_string).collect::<Vec<_>>(), [
+            "New grade posted in AP Chemistry: Unit 1 Test 80/100",
+            "New grade posted in AP Chemistry: Lab Report 19/20",
+            "New assignment in AP Chemistry: Final Exam",
+        ]);
+        assert_eq!(events[4], GradeEvent::CourseAdded { class_name: String::from("Teacher Aide") });
+        assert_eq!(events[5], GradeEvent::CourseDropped { class_name: String::from("Teacher Aide") });
+        assert_eq!(events.len(), 6);
+    }
+
+    #[test]
+    fn schedule_changes() {
+        let old: StudentClassSchedule = de::from_str(include_str!("../tests/fixtures/schedule_full.xml")).unwrap();
+        let mut new = old.clone();
+        let classes = &mut new.schedule.class_list.class;
+        classes[0].room_name = String::from("210");
+        let mut lab = classes[0].clone();
+        lab.period = 2;
+        classes.push(lab);
+
+        let events = old.diff(&new);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].to_string(), "AP Chemistry in period 1 now meets in 210 with Smith, J");
+        assert_eq!(events[1].to_string(), "Added AP Chemistry in period 2");
+
+        let dropped = new.diff(&old);
+        assert_eq!(dropped[1], ScheduleEvent::ClassDropped { class: new.schedule.class_list.class[1].clone() });
+    }
+
+    #[test]
+    fn attendance_changes() {
+        let old: AttData = de::from_str(include_str!("../tests/fixtures/attendance_absence_without_note.xml")).unwrap();
+        let mut new = old.clone();
+        new.attendance.absences.absence.remove(0);
+        new.attendance.absences.absence[0].reason = String::from("Excused");
+
+        let events = old.diff(&new);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].to_string(), "Absence updated on 9/12/2019: Excused");
+        assert_eq!(events[1].to_string(), "Absence removed on 9/4/2019");
+    }
+}
@@ -1,14 +1,16 @@
 //! Enums containing types of information which can be used for various API calls
+use crate::model::date::Date;
+use std::{fmt, str::FromStr};
 
 /// Service handles
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum WebServiceHandle {
     PXPWebServices,
     HDInfoServices,
 }
 
 /// Various methods necessary to retrieve different types of API data
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Method {
     Attendance,
     StudentHealthInfo,
@@ -46,6 +48,131 @@ pub enum Method {
     StudentClassList,
 }
 
+impl WebServiceHandle {
+    /// POST request endpoint serving the handle (ProcessWebServiceRequest is redundant for SOAP requests,
+    /// in this case since we are solely making regular POST requests it is required)
+    pub fn endpoint(self) -> &'static str {
+        match self {
+            WebServiceHandle::PXPWebServices => "/Service/PXPCommunication.asmx/ProcessWebServiceRequest",
+            WebServiceHandle::HDInfoServices => "/Service/HDInfoCommunication.asmx/ProcessWebServiceRequest",
+        }
+    }
+
+    /// Endpoint serving a handle given by name, unknown handles are assumed to be served by PXPCommunication
+    pub fn endpoint_of(name: &str) -> &'static str {
+        if name.eq_ignore_ascii_case(WebServiceHandle::HDInfoServices.into()) {
+            WebServiceHandle::HDInfoServices.endpoint()
+        } else {
+            WebServiceHandle::PXPWebServices.endpoint()
+        }
+    }
+}
+
+impl Method {
+    /// Every method of the service
+    pub const ALL: [Method; 34] = [
+        Method::Attendance,
+        Method::StudentHealthInfo,
+        Method::GetStudentDocumentInitialData,
+        Method::GradeBook,
+        Method::StudentCalendar,
+        Method::TestWebServiceURL,
+        Method::ChildList,
+        Method::GetSupportedLanguages,
+        Method::GetContentOfAttachedDoc,
+        Method::StudentCalendarAssignmentDetails,
+        Method::SaveSoundFileData,
+        Method::UploadGBDocDataForStudentAssignment,
+        Method::StudentHWNotes,
+        Method::UpdateStudentHWNotes,
+        Method::StudentInfo,
+        Method::UpdatePXPMessage,
+        Method::StudentSchoolInfo,
+        Method::UpdateDeviceToken,
+        Method::StudentDisciplineInfo,
+        Method::GenerateAuthToken,
+        Method::StudentConference,
+        Method::GetMatchingDistrictList,
+        Method::StudentFee,
+        Method::PXPContentClassWebSiteGetFileXML,
+        Method::GetPXPMessages,
+        Method::GetContentUserDefinedModule,
+        Method::GetSoundFileData,
+        Method::GetAttachedDocToAssignment,
+        Method::GetClassWebSiteData,
+        Method::GetContentOfGBAttachedDoc,
+        Method::GetReportCardInitialData,
+        Method::GetReportCardDocumentData,
+        Method::GetSpecialEdData,
+        Method::StudentClassList,
+    ];
+
+    /// The `WebServiceHandle` the method is served by
+    pub fn web_service_handle(self) -> WebServiceHandle {
+        match self {
+            Method::GetMatchingDistrictList => WebServiceHandle::HDInfoServices,
+            Method::Attendance
+            | Method::StudentHealthInfo
+            | Method::GetStudentDocumentInitialData
+            | Method::GradeBook
+            | Method::StudentCalendar
+            | Method::TestWebServiceURL
+            | Method::ChildList
+            | Method::GetSupportedLanguages
+            | Method::GetContentOfAttachedDoc
+            | Method::StudentCalendarAssignmentDetails
+            | Method::SaveSoundFileData
+            | Method::UploadGBDocDataForStudentAssignment
+            | Method::StudentHWNotes
+            | Method::UpdateStudentHWNotes
+            | Method::StudentInfo
+            | Method::UpdatePXPMessage
+            | Method::StudentSchoolInfo
+            | Method::UpdateDeviceToken
+            | Method::StudentDisciplineInfo
+            | Method::GenerateAuthToken
+            | Method::StudentConference
+            | Method::StudentFee
+            | Method::PXPContentClassWebSiteGetFileXML
+            | Method::GetPXPMessages
+            | Method::GetContentUserDefinedModule
+            | Method::GetSoundFileData
+            | Method::GetAttachedDocToAssignment
+            | Method::GetClassWebSiteData
+            | Method::GetContentOfGBAttachedDoc
+            | Method::GetReportCardInitialData
+            | Method::GetReportCardDocumentData
+            | Method::GetSpecialEdData
+            | Method::StudentClassList => WebServiceHandle::PXPWebServices,
+        }
+    }
+}
+
+impl FromStr for Method {
+    type Err = ParseMethodError;
+
+    /// Parses a method from its service name, ignoring case
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Method::ALL
+            .iter()
+            .copied()
+            .find(|m| <&str>::from(*m).eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| ParseMethodError(s.to_owned()))
+    }
+}
+
+/// Error returned when a string does not name a `Method`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseMethodError(String);
+
+impl fmt::Display for ParseMethodError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unknown method: {}", self.0)
+    }
+}
+
+impl std::error::Error for ParseMethodError {}
+
 /// Language codes for xml responses
 #[derive(Debug, PartialEq, PartialOrd)]
 pub enum Language {
@@ -53,6 +180,13 @@ pub enum Language {
     Spanish = 1,
 }
 
+/// Mobile platforms a push notification device token can be registered for
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Platform {
+    Android,
+    IOS,
+}
+
 /// Different types of parameters needed for certain methods
 #[derive(Debug, PartialEq)]
 pub enum ParamType<'a> {
@@ -61,9 +195,10 @@ pub enum ParamType<'a> {
     HealthVisits(bool),
     HealthImmunizations(bool),
     ReportPeriod(u64),
+    TermIndex(u8),
     ConcurrentSchOrgYearGU(&'a str),
     LoadAllTerms,
-    RequestDate(&'a str),
+    RequestDate(Date),
     AssignmentID(&'a str),
     LanguageCode(u64),
     ClassGU(&'a str),
@@ -72,43 +207,59 @@ pub enum ParamType<'a> {
     GBDocumentData(&'a str),
     Key,
     MatchToDistrictZipCode(&'a str),
+    DeviceToken(&'a str),
+    DeviceType(Platform),
+    RegisterDevice(bool),
 }
 
-impl<'p> ToString for ParamType<'p> {
-    fn to_string(&self) -> String {
+impl<'p> fmt::Display for ParamType<'p> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            ParamType::ChildIntID(id) => format!("<ChildIntID>{}</ChildIntID>", id),
-            ParamType::HealthConditions(c) => format!("<HealthConditions>{}</HealthConditions>", c),
-            ParamType::HealthVisits(v) => format!("<HealthVisits>{}</HealthVisits>", v),
-            ParamType::HealthImmunizations(imm) => format!("<HealthImmunizations>{}</HealthImmunizations>", imm),
-            ParamType::ConcurrentSchOrgYearGU(gu) => format!("<ConcurrentSchOrgYearGU>{}</ConcurrentSchOrgYearGU>", gu),
-            ParamType::ReportPeriod(period) => format!("<ReportPeriod>{}</ReportPeriod>", period),
-            ParamType::StudentClassList(list) => format!("<StudentClassList>{}</StudentClassList>", list),
-            ParamType::RequestDate(date) => format!("<RequestDate>{}</RequestDate>", date),
-            ParamType::LanguageCode(lang_id) => format!("<LanguageCode>{}</LanguageCode>", lang_id),
-            ParamType::ClassGU(class_gu) => format!("<ClassGU>{}</ClassGU>", class_gu),
-            ParamType::AssignmentID(id) => format!("<AssignmentID>{}</AssignmentID>", id),
-            ParamType::SoundFileListing(listing) => format!("<SoundFileListing>{}</SoundFileListing>", listing),
-            ParamType::GBDocumentData(data) => format!("<GBDocumentData>{}</GBDocumentData>", data),
-            ParamType::Key => String::from("<Key>5E4B7859-B805-474B-A833-FDB15D205D40</Key>"),
-            ParamType::MatchToDistrictZipCode(zip) => format!("<MatchToDistrictZipCode>{}</MatchToDistrictZipCode>", zip),
-            ParamType::LoadAllTerms => String::from("<LoadAllTerms>true</LoadAllTerms>")
+            ParamType::ChildIntID(id) => write!(f, "<ChildIntID>{}</ChildIntID>", id),
+            ParamType::HealthConditions(c) => write!(f, "<HealthConditions>{}</HealthConditions>", c),
+            ParamType::HealthVisits(v) => write!(f, "<HealthVisits>{}</HealthVisits>", v),
+            ParamType::HealthImmunizations(imm) => write!(f, "<HealthImmunizations>{}</HealthImmunizations>", imm),
+            ParamType::ConcurrentSchOrgYearGU(gu) => write!(f, "<ConcurrentSchOrgYearGU>{}</ConcurrentSchOrgYearGU>", gu),
+            ParamType::ReportPeriod(period) => write!(f, "<ReportPeriod>{}</ReportPeriod>", period),
+            ParamType::TermIndex(term) => write!(f, "<TermIndex>{}</TermIndex>", term),
+            ParamType::StudentClassList(list) => write!(f, "<StudentClassList>{}</StudentClassList>", list),
+            ParamType::RequestDate(date) => write!(f, "<RequestDate>{}</RequestDate>", date),
+            ParamType::LanguageCode(lang_id) => write!(f, "<LanguageCode>{}</LanguageCode>", lang_id),
+            ParamType::ClassGU(class_gu) => write!(f, "<ClassGU>{}</ClassGU>", class_gu),
+            ParamType::AssignmentID(id) => write!(f, "<AssignmentID>{}</AssignmentID>", id),
+            ParamType::SoundFileListing(listing) => write!(f, "<SoundFileListing>{}</SoundFileListing>", listing),
+            ParamType::GBDocumentData(data) => write!(f, "<GBDocumentData>{}</GBDocumentData>", data),
+            ParamType::Key => f.write_str("<Key>5E4B7859-B805-474B-A833-FDB15D205D40</Key>"),
+            ParamType::MatchToDistrictZipCode(zip) => write!(f, "<MatchToDistrictZipCode>{}</MatchToDistrictZipCode>", zip),
+            ParamType::LoadAllTerms => f.write_str("<LoadAllTerms>true</LoadAllTerms>"),
+            ParamType::DeviceToken(token) => write!(f, "<DeviceToken>{}</DeviceToken>", token),
+            ParamType::DeviceType(platform) => write!(f, "<DeviceType>{}</DeviceType>", <&str>::from(*platform)),
+            ParamType::RegisterDevice(register) => write!(f, "<RegisterDevice>{}</RegisterDevice>", register),
         }
     }
 }
 
-impl<'w> Into<&'w str> for WebServiceHandle {
-    fn into(self) -> &'w str {
-        match self {
+impl From<WebServiceHandle> for &'static str {
+    fn from(handle: WebServiceHandle) -> &'static str {
+        match handle {
             WebServiceHandle::PXPWebServices => "PXPWebServices",
             WebServiceHandle::HDInfoServices => "HDInfoServices",
         }
     }
 }
 
-impl<'m> Into<&'m str> for Method {
-    fn into(self) -> &'m str {
-        match self {
+impl From<Platform> for &'static str {
+    fn from(platform: Platform) -> &'static str {
+        match platform {
+            Platform::Android => "Android",
+            Platform::IOS => "iOS",
+        }
+    }
+}
+
+impl From<Method> for &'static str {
+    fn from(method: Method) -> &'static str {
+        match method {
             Method::Attendance => "Attendance",
             Method::StudentHealthInfo => "StudentHealthInfo",
             Method::GetStudentDocumentInitialData => "GetStudentDocumentInitialData",
@@ -156,4 +307,18 @@ mod tests {
         let param = ParamType::ReportPeriod(7);
         assert_eq!(&param.to_string()[..], "<ReportPeriod>7</ReportPeriod>")
     }
+
+    #[test]
+    fn handle_mapping() {
+        assert_eq!(Method::GetMatchingDistrictList.web_service_handle(), WebServiceHandle::HDInfoServices);
+        assert_eq!(Method::GradeBook.web_service_handle(), WebServiceHandle::PXPWebServices);
+    }
+
+    #[test]
+    fn method_names() {
+        assert_eq!("gradebook".parse(), Ok(Method::GradeBook));
+        assert_eq!(" StudentClassList".parse(), Ok(Method::StudentClassList));
+        assert!("Grades".parse::<Method>().is_err());
+        assert!(Method::ALL.iter().all(|m| <&str>::from(*m).parse() == Ok(*m)));
+    }
 }
@@ -1,12 +1,45 @@
 //! Errors which may occur during xml parsing or when handling a web request
 use std::fmt;
 
+/// Lowercased `ERROR_MESSAGE`s of `RT_ERROR` responses to rejected logins
+const CREDENTIAL_MESSAGES: [&str; 2] = ["invalid user id or password", "the user name or password is incorrect"];
+
 /// General StudentVUE error
 #[derive(Debug)]
 pub enum VueError {
     Format(std::fmt::Error),
     Xml(quick_xml::DeError),
-    Request(reqwest::Error)
+    Request(reqwest::Error),
+    Io(std::io::Error),
+    Transport(Box<dyn std::error::Error + Send + Sync>),
+    /// The service rejected the credentials, such as an invalid user id or password
+    Credentials(String),
+    /// The service answered with an `RT_ERROR` other than a credential error
+    Service(String),
+    /// A parameter name is not a valid XML element name
+    InvalidName(String),
+    /// Reading or writing local storage failed, such as the SQLite database of the `sqlite` feature
+    Storage(Box<dyn std::error::Error + Send + Sync>),
+    /// A call failed on every attempt allowed by the `RetryPolicy`, `last` holds the error of the final attempt
+    RetriesExhausted { attempts: u32, last: Box<VueError> },
+}
+
+impl VueError {
+    /// Classifies the `ERROR_MESSAGE` of an `RT_ERROR` response, only the messages Edupoint sends for rejected
+    /// logins are credential errors
+    pub fn from_service_message(message: &str) -> VueError {
+        let lower = message.to_lowercase();
+        if CREDENTIAL_MESSAGES.iter().any(|m| lower.starts_with(m)) {
+            VueError::Credentials(message.to_owned())
+        } else {
+            VueError::Service(message.to_owned())
+        }
+    }
+
+    /// Whether the service rejected the credentials
+    pub fn is_credentials(&self) -> bool {
+        matches!(self, VueError::Credentials(_))
+    }
 }
 
 impl From<std::fmt::Error> for VueError {
@@ -27,14 +60,55 @@ impl From<reqwest::Error> for VueError {
     }
 }
 
+impl From<std::io::Error> for VueError {
+    fn from(err: std::io::Error) -> VueError {
+        VueError::Io(err)
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl From<rusqlite::Error> for VueError {
+    fn from(err: rusqlite::Error) -> VueError {
+        VueError::Storage(Box::new(err))
+    }
+}
+
 impl fmt::Display for VueError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             VueError::Request(e) => write!(f, "An error occured during the server request: {}", e),
             VueError::Format(e) => write!(f, "Formatting error occured: {}", e),
             VueError::Xml(e) => write!(f, "XML parsing error occured{}", e),
+            VueError::Io(e) => write!(f, "I/O error occured: {}", e),
+            VueError::Transport(e) => write!(f, "An error occured in the transport: {}", e),
+            VueError::Credentials(msg) => write!(f, "The credentials were rejected: {}", msg),
+            VueError::Service(msg) => write!(f, "The service returned an error: {}", msg),
+            VueError::InvalidName(name) => write!(f, "Invalid parameter name: {:?}", name),
+            VueError::Storage(e) => write!(f, "Storage error occured: {}", e),
+            VueError::RetriesExhausted { attempts, last } => write!(f, "Request failed after {} attempts: {}", attempts, last),
         }
     }
 }
 
-impl std::error::Error for VueError {}
\ No newline at end of file
+impl std::error::Error for VueError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn service_messages() {
+        for message in ["Invalid user id or password", "The user name or password is incorrect."] {
+            assert!(matches!(VueError::from_service_message(message), VueError::Credentials(_)));
+        }
+
+        for message in [
+            "Account settings not available for this school",
+            "The password reset feature is not enabled for this district",
+            "Student user id not found in current school year",
+            "Object reference not set to an instance of an object.",
+        ] {
+            assert!(matches!(VueError::from_service_message(message), VueError::Service(_)), "{}", message);
+        }
+    }
+}
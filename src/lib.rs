@@ -5,17 +5,37 @@
 //! in a convenient format.
 //!
 //! ## Features
-//! - Asynchronous
+//! - Asynchronous, with an optional blocking client behind the `blocking` feature
 //! - Retrieve grades, school information, schedules, attendance
-//! - Support for various API methods
+//! - Support for various API methods, including calling methods by name and reading the response as a generic XML tree
+//! - GPA, category weighted average and what-if calculations over the gradebook
+//! - Change detection between snapshots of grades, attendance and schedules
+//! - Configurable retries with exponential backoff for transient failures
+//! - Request rate and concurrency limits per district, shared across accounts
+//! - Concurrent batch fetching for many accounts across districts
+//! - Optional response caching with a time to live per method
+//! - Polling watcher streaming new grades, absences, messages and calendar events
+//! - SQLite persistence with grade history behind the `sqlite` feature
 
 pub use client::ParamBuilder;
 pub use client::Client;
 pub use enums::Method;
-pub use request::WebHandle;
+pub use request::{Transport, WebHandle};
 
+pub mod analysis;
+pub mod batch;
+pub mod cache;
 pub mod client;
+pub mod diff;
 pub mod enums;
 pub mod error;
+pub mod limit;
 pub mod request;
-pub mod model;
\ No newline at end of file
+pub mod retry;
+pub mod model;
+pub mod watch;
+pub mod xml;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+#[cfg(feature = "sqlite")]
+pub mod storage;
@@ -1,27 +1,160 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use super::date::Date;
+use std::collections::BTreeMap;
 
-#[derive(Debug, Deserialize, PartialEq)]
-#[serde(rename_all = "PascalCase")]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all(deserialize = "PascalCase"))]
 pub struct Absence {
-    pub absence_date: String,
+    #[serde(alias = "absence_date")]
+    pub absence_date: Date,
+    #[serde(alias = "reason", default)]
     pub reason: String,
+    #[serde(alias = "note", default)]
     pub note: String,
+    #[serde(alias = "daily_icon_name", default)]
+    pub daily_icon_name: String,
+    #[serde(alias = "periods", default)]
+    pub periods: Periods,
 }
 
-#[derive(Debug, Deserialize, PartialEq)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Periods {
+    #[serde(rename(deserialize = "Period"), alias = "period", default)]
+    pub period: Vec<Period>,
+}
+
+/// Attendance entry for a single period of an absence
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all(deserialize = "PascalCase"))]
+pub struct Period {
+    #[serde(alias = "number")]
+    pub number: u8,
+    #[serde(alias = "name", default)]
+    pub name: String,
+    #[serde(alias = "reason", default)]
+    pub reason: String,
+    #[serde(alias = "course", default)]
+    pub course: String,
+    #[serde(alias = "staff", default)]
+    pub staff: String,
+    #[serde(rename(deserialize = "StaffEMail"), alias = "staff_email", default)]
+    pub staff_email: String,
+    #[serde(alias = "icon_name", default)]
+    pub icon_name: String,
+    #[serde(alias = "school_name", default)]
+    pub school_name: String,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Absences {
-    #[serde(rename = "Absence")]
+    #[serde(rename(deserialize = "Absence"), alias = "absence", default)]
     pub absence: Vec<Absence>,
 }
 
-#[derive(Debug, Deserialize, PartialEq)]
-#[serde(rename_all = "PascalCase")]
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PeriodTotals {
+    #[serde(rename(deserialize = "PeriodTotal"), alias = "period", default)]
+    pub period: Vec<PeriodTotal>,
+}
+
+/// Number of occurrences of an attendance category for a period
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all(deserialize = "PascalCase"))]
+pub struct PeriodTotal {
+    #[serde(alias = "number")]
+    pub number: u8,
+    #[serde(alias = "total")]
+    pub total: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all(deserialize = "PascalCase"))]
 pub struct Attendance {
+    /// Whether attendance is taken per "Period" or per "Daily"
+    #[serde(rename(deserialize = "Type"), alias = "attendance_type", default)]
+    pub attendance_type: String,
+    #[serde(alias = "start_period", default)]
+    pub start_period: u8,
+    #[serde(alias = "end_period", default)]
+    pub end_period: u8,
+    #[serde(alias = "period_count", default)]
+    pub period_count: u8,
+    #[serde(alias = "school_name", default)]
+    pub school_name: String,
+    #[serde(alias = "absences", default)]
     pub absences: Absences,
+    #[serde(alias = "total_excused", default)]
+    pub total_excused: PeriodTotals,
+    #[serde(alias = "total_tardies", default)]
+    pub total_tardies: PeriodTotals,
+    #[serde(alias = "total_unexcused", default)]
+    pub total_unexcused: PeriodTotals,
+    #[serde(alias = "total_activities", default)]
+    pub total_activities: PeriodTotals,
+    #[serde(alias = "total_unexcused_tardies", default)]
+    pub total_unexcused_tardies: PeriodTotals,
 }
 
-#[derive(Debug, Deserialize, PartialEq)]
-#[serde(rename_all = "PascalCase")]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all(deserialize = "PascalCase"))]
 pub struct AttData {
+    #[serde(alias = "attendance")]
     pub attendance: Attendance
 }
+
+/// Attendance totals of a single course
+#[derive(Debug, Default, Clone, Serialize, PartialEq)]
+pub struct CourseTotals {
+    pub period: u8,
+    /// Course taught in the period, empty if no attendance entry names it
+    pub course: String,
+    pub excused: u32,
+    pub tardies: u32,
+    pub unexcused: u32,
+    pub activities: u32,
+    pub unexcused_tardies: u32,
+}
+
+impl Attendance {
+    /// Every period entry along with the date it was recorded on
+    pub fn period_entries(&self) -> impl Iterator<Item = (Date, &Period)> {
+        self.absences.absence
+            .iter()
+            .flat_map(|a| a.periods.period.iter().map(move |p| (a.absence_date, p)))
+    }
+
+    /// Combines the per period totals reported by the server with the course names of the period entries
+    pub fn course_totals(&self) -> Vec<CourseTotals> {
+        let mut totals: BTreeMap<u8, CourseTotals> = BTreeMap::new();
+
+        type Field = fn(&mut CourseTotals) -> &mut u32;
+        let categories: [(&PeriodTotals, Field); 5] = [
+            (&self.total_excused, |t| &mut t.excused),
+            (&self.total_tardies, |t| &mut t.tardies),
+            (&self.total_unexcused, |t| &mut t.unexcused),
+            (&self.total_activities, |t| &mut t.activities),
+            (&self.total_unexcused_tardies, |t| &mut t.unexcused_tardies),
+        ];
+
+        for (category, field) in categories.iter() {
+            for p in category.period.iter() {
+                let entry = totals.entry(p.number).or_insert_with(|| CourseTotals {
+                    period: p.number,
+                    ..CourseTotals::default()
+                });
+                *field(entry) += p.total;
+            }
+        }
+
+        // later entries win so course changes during the term reflect the current course
+        let mut entries: Vec<_> = self.period_entries().collect();
+        entries.sort_by_key(|(date, _)| *date);
+        for (_, p) in entries.into_iter().filter(|(_, p)| !p.course.is_empty()) {
+            if let Some(entry) = totals.get_mut(&p.number) {
+                entry.course = p.course.clone();
+            }
+        }
+
+        totals.into_values().collect()
+    }
+}
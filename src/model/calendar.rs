@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+use super::{date::{Date, Time}, de_util};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StudentCalendar {
+    #[serde(rename(deserialize = "CalendarListing"), alias = "calendar")]
+    pub calendar: CalendarListing,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all(deserialize = "PascalCase"))]
+pub struct CalendarListing {
+    #[serde(rename(deserialize = "SchoolBegDate"), alias = "school_begin_date", default, deserialize_with = "de_util::optional")]
+    pub school_begin_date: Option<Date>,
+    #[serde(alias = "school_end_date", default, deserialize_with = "de_util::optional")]
+    pub school_end_date: Option<Date>,
+    /// First day of the month the listing was retrieved for
+    #[serde(rename(deserialize = "MonthBegDate"), alias = "month_begin_date", default, deserialize_with = "de_util::optional")]
+    pub month_begin_date: Option<Date>,
+    #[serde(alias = "month_end_date", default, deserialize_with = "de_util::optional")]
+    pub month_end_date: Option<Date>,
+    #[serde(alias = "event_lists", default)]
+    pub event_lists: EventLists,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EventLists {
+    #[serde(rename(deserialize = "EventList"), alias = "event", default)]
+    pub event: Vec<Event>,
+}
+
+/// Calendar entry such as a due assignment, holiday or school event
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all(deserialize = "PascalCase"))]
+pub struct Event {
+    #[serde(alias = "date")]
+    pub date: Date,
+    #[serde(alias = "title", default)]
+    pub title: String,
+    /// Kind of entry, e.g. "Assignment", "Holiday" or "Regular"
+    #[serde(alias = "day_type", default)]
+    pub day_type: String,
+    #[serde(alias = "start_time", default, deserialize_with = "de_util::optional")]
+    pub start_time: Option<Time>,
+    /// Gradebook id of the assignment for assignment entries
+    #[serde(rename(deserialize = "AGU"), alias = "assignment_gu", default)]
+    pub assignment_gu: String,
+    #[serde(alias = "icon", default)]
+    pub icon: String,
+}
@@ -0,0 +1,345 @@
+//! Calendar date and time of day used throughout the models
+//!
+//! StudentVUE formats dates as `M/D/YYYY`, `Date` parses that format (along with ISO 8601 `YYYY-MM-DD`) during
+//! deserialization so dates can be sorted and compared directly. Bell times such as `7:30 AM` are parsed into
+//! `Time`. Conversions to and from `chrono::NaiveDate` are available behind the `chrono` feature.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    fmt,
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Calendar date without a time zone
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    year: i32,
+    month: u8,
+    day: u8,
+}
+
+/// Time of day with minute precision
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Time {
+    hour: u8,
+    minute: u8,
+}
+
+/// Error returned when a string is not a valid date or time, holding the rejected string
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseDateError {
+    Date(String),
+    Time(String),
+}
+
+impl Date {
+    /// Creates a `Date`, returning `None` if the month or day is out of range
+    pub fn new(year: i32, month: u8, day: u8) -> Option<Self> {
+        if month == 0 || month > 12 || day == 0 || day > days_in_month(year, month) {
+            return None;
+        }
+
+        Some(Date { year, month, day })
+    }
+
+    #[inline]
+    pub fn year(&self) -> i32 {
+        self.year
+    }
+
+    #[inline]
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    #[inline]
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+
+    /// The current date in UTC
+    pub fn today() -> Self {
+        Date::from_unix_days(unix_now().div_euclid(86_400))
+    }
+
+    /// The current date in the local time zone, falling back to UTC on platforms other than unix
+    pub fn local_today() -> Self {
+        let secs = unix_now();
+        Date::from_unix_days((secs + local_offset(secs)).div_euclid(86_400))
+    }
+
+    // Converts days since 1970-01-01 to a date of the proleptic Gregorian calendar
+    fn from_unix_days(days: i64) -> Self {
+        let days = days + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days.rem_euclid(146_097);
+        let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+        let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+        Date { year: year as i32, month: month as u8, day: day as u8 }
+    }
+
+    /// Formats the date as ISO 8601 `YYYY-MM-DD`
+    pub fn to_iso_string(&self) -> String {
+        format!("{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+// Seconds since 1970-01-01 UTC
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64)
+}
+
+// Offset of the local time zone from UTC in seconds at the given unix time
+#[cfg(unix)]
+fn local_offset(secs: i64) -> i64 {
+    let time = secs as libc::time_t;
+    // SAFETY: `tm` is plain data for which zeroes are valid, and localtime_r only writes to it for this call
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    if unsafe { libc::localtime_r(&time, &mut tm) }.is_null() {
+        0
+    } else {
+        tm.tm_gmtoff as i64
+    }
+}
+
+#[cfg(not(unix))]
+fn local_offset(_secs: i64) -> i64 {
+    0
+}
+
+fn days_in_month(year: i32, month: u8) -> u8 {
+    match month {
+        2 if (year % 4 == 0 && year % 100 != 0) || year % 400 == 0 => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+impl FromStr for Date {
+    type Err = ParseDateError;
+
+    /// Parses `M/D/YYYY` (two digit years are taken as 20YY) or `YYYY-MM-DD`, ignoring any trailing time
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseDateError::Date(s.to_owned());
+        let date = s.split_whitespace().next().ok_or_else(err)?;
+
+        let mut parts = date.split(['/', '-']);
+        let mut next = || parts.next().and_then(|p| p.parse::<i32>().ok()).ok_or_else(err);
+        let (first, second, third) = (next()?, next()?, next()?);
+
+        let (year, month, day) = if date.contains('/') {
+            (if third < 100 { third + 2000 } else { third }, first, second)
+        } else {
+            (first, second, third)
+        };
+
+        if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+            return Err(err());
+        }
+
+        Date::new(year, month as u8, day as u8).ok_or_else(err)
+    }
+}
+
+impl fmt::Display for Date {
+    /// Formats the date the way StudentVUE expects it, `M/D/YYYY`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}/{}", self.month, self.day, self.year)
+    }
+}
+
+impl Time {
+    /// Creates a `Time` from a 24 hour clock, returning `None` if the hour or minute is out of range
+    pub fn new(hour: u8, minute: u8) -> Option<Self> {
+        if hour > 23 || minute > 59 {
+            return None;
+        }
+
+        Some(Time { hour, minute })
+    }
+
+    #[inline]
+    pub fn hour(&self) -> u8 {
+        self.hour
+    }
+
+    #[inline]
+    pub fn minute(&self) -> u8 {
+        self.minute
+    }
+}
+
+impl FromStr for Time {
+    type Err = ParseDateError;
+
+    /// Parses 12 hour (`7:30 AM`) or 24 hour (`13:05`) clock times, ignoring seconds
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseDateError::Time(s.to_owned());
+        let upper = s.trim().to_ascii_uppercase();
+        let (clock, meridiem) = match upper.strip_suffix("AM").or_else(|| upper.strip_suffix("PM")) {
+            Some(clock) => (clock.trim(), Some(upper.ends_with("PM"))),
+            None => (upper.as_str(), None),
+        };
+
+        let mut parts = clock.split(':');
+        let mut next = || parts.next().and_then(|p| p.parse::<u8>().ok()).ok_or_else(err);
+        let (hour, minute) = (next()?, next()?);
+
+        let hour = match meridiem {
+            Some(_) if hour == 0 || hour > 12 => return Err(err()),
+            Some(pm) => hour % 12 + if pm { 12 } else { 0 },
+            None => hour,
+        };
+
+        Time::new(hour, minute).ok_or_else(err)
+    }
+}
+
+impl fmt::Display for Time {
+    /// Formats the time on a 12 hour clock the way StudentVUE does, `7:30 AM`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let hour = match self.hour % 12 {
+            0 => 12,
+            h => h,
+        };
+
+        write!(f, "{}:{:02} {}", hour, self.minute, if self.hour < 12 { "AM" } else { "PM" })
+    }
+}
+
+impl fmt::Display for ParseDateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseDateError::Date(s) => write!(f, "Invalid date: {:?}", s),
+            ParseDateError::Time(s) => write!(f, "Invalid time: {:?}", s),
+        }
+    }
+}
+
+impl std::error::Error for ParseDateError {}
+
+impl Serialize for Date {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_iso_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Date {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = String::deserialize(deserializer)?;
+        raw.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl Serialize for Time {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("{:02}:{:02}", self.hour, self.minute))
+    }
+}
+
+impl<'de> Deserialize<'de> for Time {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = String::deserialize(deserializer)?;
+        raw.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<Date> for chrono::NaiveDate {
+    fn from(date: Date) -> chrono::NaiveDate {
+        chrono::NaiveDate::from_ymd_opt(date.year, date.month.into(), date.day.into())
+            .expect("Date is always valid")
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<chrono::NaiveDate> for Date {
+    fn from(date: chrono::NaiveDate) -> Date {
+        use chrono::Datelike;
+
+        Date {
+            year: date.year(),
+            month: date.month() as u8,
+            day: date.day() as u8,
+        }
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<Time> for chrono::NaiveTime {
+    fn from(time: Time) -> chrono::NaiveTime {
+        chrono::NaiveTime::from_hms_opt(time.hour.into(), time.minute.into(), 0)
+            .expect("Time is always valid")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsing() {
+        assert_eq!("9/4/2019".parse(), Ok(Date::new(2019, 9, 4).unwrap()));
+        assert_eq!("1/23/19".parse(), Ok(Date::new(2019, 1, 23).unwrap()));
+        assert_eq!("2019-09-04".parse(), Ok(Date::new(2019, 9, 4).unwrap()));
+        assert_eq!("9/4/2019 12:00:00 AM".parse(), Ok(Date::new(2019, 9, 4).unwrap()));
+        assert!("2/30/2019".parse::<Date>().is_err());
+        assert!("".parse::<Date>().is_err());
+    }
+
+    #[test]
+    fn unix_days() {
+        assert_eq!(Date::from_unix_days(0), Date::new(1970, 1, 1).unwrap());
+        assert_eq!(Date::from_unix_days(11_016), Date::new(2000, 2, 29).unwrap());
+        assert_eq!(Date::from_unix_days(19_358), Date::new(2023, 1, 1).unwrap());
+        assert_eq!(Date::from_unix_days(-1), Date::new(1969, 12, 31).unwrap());
+        assert!(Date::today() >= Date::new(2024, 1, 1).unwrap());
+
+        // time zones are at most 14 hours away from UTC
+        let today = unix_now().div_euclid(86_400);
+        assert!((today - 1..=today + 1).any(|days| Date::from_unix_days(days) == Date::local_today()));
+    }
+
+    #[test]
+    fn ordering_and_format() {
+        let (a, b): (Date, Date) = ("12/31/2019".parse().unwrap(), "1/2/2020".parse().unwrap());
+
+        assert!(a < b);
+        assert_eq!(b.to_string(), "1/2/2020");
+        assert_eq!(serde_json::to_value(b).unwrap(), "2020-01-02");
+    }
+
+    #[test]
+    fn times() {
+        assert_eq!("7:30 AM".parse(), Ok(Time::new(7, 30).unwrap()));
+        assert_eq!("12:05 pm".parse(), Ok(Time::new(12, 5).unwrap()));
+        assert_eq!("12:00 AM".parse(), Ok(Time::new(0, 0).unwrap()));
+        assert_eq!("13:05:00".parse(), Ok(Time::new(13, 5).unwrap()));
+        assert!("13:05 PM".parse::<Time>().is_err());
+        assert_eq!("25:00".parse::<Time>().unwrap_err().to_string(), "Invalid time: \"25:00\"");
+        assert_eq!("2/30/2020".parse::<Date>().unwrap_err(), ParseDateError::Date(String::from("2/30/2020")));
+
+        let time = Time::new(14, 5).unwrap();
+        assert_eq!(time.to_string(), "2:05 PM");
+        assert_eq!(serde_json::to_value(time).unwrap(), "14:05");
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn chrono_roundtrip() {
+        let date = Date::new(2020, 2, 29).unwrap();
+        let naive = chrono::NaiveDate::from(date);
+
+        assert_eq!(naive, chrono::NaiveDate::from_ymd_opt(2020, 2, 29).unwrap());
+        assert_eq!(Date::from(naive), date);
+    }
+}
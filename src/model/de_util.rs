@@ -0,0 +1,66 @@
+//! Serde helpers for loosely formatted fields
+//!
+//! Besides the strings sent by the service, the helpers accept the values the models serialize to, such as
+//! numbers and `null`, so serialized models deserialize back.
+
+use serde::{Deserialize, Deserializer};
+use std::{fmt::Display, str::FromStr};
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Number {
+    Value(f32),
+    Text(String),
+}
+
+/// Parses a number which may be empty or hold a placeholder such as "N/A", yielding `None` in that case
+pub fn lenient_f32<'de, D>(deserializer: D) -> Result<Option<f32>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(match Option::<Number>::deserialize(deserializer)? {
+        Some(Number::Value(value)) => Some(value),
+        Some(Number::Text(raw)) => raw.trim().trim_end_matches('%').parse().ok(),
+        None => None,
+    })
+}
+
+/// Parses a value such as a `Date` or `Time` which may be left empty or `null`, yielding `None` in that case
+pub fn optional<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    match Option::<String>::deserialize(deserializer)? {
+        Some(raw) if !raw.trim().is_empty() => raw.parse().map(Some).map_err(serde::de::Error::custom),
+        _ => Ok(None),
+    }
+}
+
+/// Base64 encoded binary data, empty strings and `null` are treated as missing
+pub mod base64_bytes {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &Option<Vec<u8>>, serializer: S) -> Result<S::Ok, S::Error> {
+        match bytes {
+            Some(bytes) => serializer.serialize_some(&STANDARD.encode(bytes)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Vec<u8>>, D::Error> {
+        let raw: String = Option::<String>::deserialize(deserializer)?
+            .unwrap_or_default()
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect();
+
+        if raw.is_empty() {
+            return Ok(None);
+        }
+
+        STANDARD.decode(raw).map(Some).map_err(serde::de::Error::custom)
+    }
+}
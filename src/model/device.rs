@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DeviceTokenResponse {
+    #[serde(rename(deserialize = "RT_RESPONSE"), alias = "response")]
+    pub response: RtResponse,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RtResponse {
+    #[serde(rename(deserialize = "RESPONSE_STATUS"), alias = "status", default)]
+    pub status: String,
+    #[serde(rename(deserialize = "RESPONSE_MESSAGE"), alias = "message", default)]
+    pub message: String,
+}
+
+impl DeviceTokenResponse {
+    /// Whether the server acknowledged the device token update
+    pub fn is_success(&self) -> bool {
+        self.response.status.eq_ignore_ascii_case("success")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quick_xml::de;
+
+    #[test]
+    fn response_parsing() {
+        let xml = r#"<string><RT_RESPONSE RESPONSE_STATUS="Success" RESPONSE_MESSAGE="Device registered" /></string>"#;
+        let res: DeviceTokenResponse = de::from_str(xml).unwrap();
+
+        assert!(res.is_success());
+        assert_eq!(res.response.message, "Device registered");
+    }
+}
@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MatchingDistricts {
+    #[serde(rename(deserialize = "DistrictLists"), alias = "lists")]
+    pub lists: DistrictLists,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DistrictLists {
+    #[serde(rename(deserialize = "DistrictInfos"), alias = "infos")]
+    pub infos: DistrictInfos,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DistrictInfos {
+    #[serde(rename(deserialize = "DistrictInfo"), alias = "district", default)]
+    pub district: Vec<DistrictInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DistrictInfo {
+    #[serde(rename(deserialize = "DistrictID"), alias = "id")]
+    pub id: String,
+    #[serde(rename(deserialize = "Name"), alias = "name")]
+    pub name: String,
+    #[serde(rename(deserialize = "Address"), alias = "address")]
+    pub address: String,
+    #[serde(rename(deserialize = "PvueURL"), alias = "url")]
+    pub url: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quick_xml::de;
+
+    #[test]
+    fn district_parsing() {
+        let xml = r#"<string><DistrictLists><DistrictInfos>
+            <DistrictInfo DistrictID="9fd2" Name="Phoenix Union High School District" Address="Phoenix AZ 85012" PvueURL="https://studentvue.phoenixunion.org" />
+            </DistrictInfos></DistrictLists></string>"#;
+        let res: MatchingDistricts = de::from_str(xml).unwrap();
+        let districts = &res.lists.infos.district;
+
+        assert_eq!(districts.len(), 1);
+        assert_eq!(districts[0].url, "https://studentvue.phoenixunion.org");
+    }
+}
@@ -1,48 +1,244 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use super::{date::Date, de_util};
 
-#[derive(Debug, Deserialize, PartialEq)]
-#[serde(rename_all = "PascalCase")]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all(deserialize = "PascalCase"))]
 pub struct GbData {
+    #[serde(alias = "gradebook")]
     pub gradebook: Gradebook,
 }
 
-#[derive(Debug, Deserialize, PartialEq)]
-#[serde(rename_all = "PascalCase")]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all(deserialize = "PascalCase"))]
 pub struct Gradebook {
+    /// Every reporting period the server advertises
+    #[serde(alias = "reporting_periods", default)]
+    pub reporting_periods: ReportingPeriods,
+    /// The reporting period the gradebook was retrieved for
+    #[serde(alias = "reporting_period", default)]
+    pub reporting_period: Option<ReportingPeriod>,
+    #[serde(alias = "courses", default)]
     pub courses: Courses,
 }
 
-#[derive(Debug, Deserialize, PartialEq)]
-#[serde(rename_all = "PascalCase")]
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReportingPeriods {
+    #[serde(rename(deserialize = "ReportPeriod"), alias = "period", default)]
+    pub period: Vec<ReportPeriod>,
+}
+
+/// Reporting period which can be passed to `Client::get_grades` through its index
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all(deserialize = "PascalCase"))]
+pub struct ReportPeriod {
+    #[serde(alias = "index")]
+    pub index: u64,
+    #[serde(rename(deserialize = "GradePeriod"), alias = "name")]
+    pub name: String,
+    #[serde(alias = "start_date", default, deserialize_with = "de_util::optional")]
+    pub start_date: Option<Date>,
+    #[serde(alias = "end_date", default, deserialize_with = "de_util::optional")]
+    pub end_date: Option<Date>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all(deserialize = "PascalCase"))]
+pub struct ReportingPeriod {
+    #[serde(rename(deserialize = "GradePeriod"), alias = "name")]
+    pub name: String,
+    #[serde(alias = "start_date", default, deserialize_with = "de_util::optional")]
+    pub start_date: Option<Date>,
+    #[serde(alias = "end_date", default, deserialize_with = "de_util::optional")]
+    pub end_date: Option<Date>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all(deserialize = "PascalCase"))]
 pub struct Courses {
+    #[serde(alias = "course", default)]
     pub course: Vec<Course>
 }
 
-#[derive(Debug, Deserialize, PartialEq)]
-#[serde(rename_all = "PascalCase")]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all(deserialize = "PascalCase"))]
 pub struct Course {
+    #[serde(alias = "period")]
     pub period: u8,
-    #[serde(rename = "Title")]
+    #[serde(rename(deserialize = "Title"), alias = "class_name")]
     pub class_name: String,
-    pub room: u32,
+    #[serde(alias = "room", default)]
+    pub room: String,
+    #[serde(alias = "staff", default)]
     pub staff: String,
-    #[serde(rename = "StaffEMail")]
+    #[serde(rename(deserialize = "StaffEMail"), alias = "staff_email", default)]
     pub staff_email: String,
-    #[serde(rename = "Marks")]
+    #[serde(rename(deserialize = "Marks"), alias = "marks", default)]
     pub marks: Vec<Marks>,
 }
 
-#[derive(Debug, Deserialize, PartialEq)]
-#[serde(rename_all = "PascalCase")]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all(deserialize = "PascalCase"))]
 pub struct Marks {
+    #[serde(alias = "mark", default)]
     pub mark: Vec<Mark>,
 }
 
-#[derive(Debug, Deserialize, PartialEq)]
-#[serde(rename_all = "PascalCase")]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all(deserialize = "PascalCase"))]
 pub struct Mark {
-    #[serde(rename = "CalculatedScoreString")]
-    pub grade: char,
-    #[serde(rename = "CalculatedScoreRaw")]
-    pub percent: f32,
+    /// Mark shown for the course such as "A+", "P" or "N/A"
+    #[serde(rename(deserialize = "CalculatedScoreString"), alias = "grade", default)]
+    pub grade: String,
+    /// Raw percentage, missing when the course has not been graded yet
+    #[serde(rename(deserialize = "CalculatedScoreRaw"), alias = "percent", default, deserialize_with = "de_util::lenient_f32")]
+    pub percent: Option<f32>,
+    #[serde(rename(deserialize = "MarkName"), alias = "name", default)]
+    pub name: String,
+    #[serde(rename(deserialize = "GradeCalculationSummary"), alias = "grade_calculation", default)]
+    pub grade_calculation: GradeCalculationSummary,
+    #[serde(alias = "assignments", default)]
+    pub assignments: Assignments,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GradeCalculationSummary {
+    #[serde(rename(deserialize = "AssignmentGradeCalc"), alias = "category", default)]
+    pub category: Vec<AssignmentGradeCalc>,
+}
+
+/// Weight and running score of an assignment category, the summary also holds a "TOTAL" row
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all(deserialize = "PascalCase"))]
+pub struct AssignmentGradeCalc {
+    #[serde(rename(deserialize = "Type"), alias = "name")]
+    pub name: String,
+    /// Weight of the category as a percentage of the course grade
+    #[serde(alias = "weight", default, deserialize_with = "de_util::lenient_f32")]
+    pub weight: Option<f32>,
+    #[serde(alias = "points", default, deserialize_with = "de_util::lenient_f32")]
+    pub points: Option<f32>,
+    #[serde(alias = "points_possible", default, deserialize_with = "de_util::lenient_f32")]
+    pub points_possible: Option<f32>,
+    #[serde(rename(deserialize = "WeightedPct"), alias = "weighted_percent", default, deserialize_with = "de_util::lenient_f32")]
+    pub weighted_percent: Option<f32>,
+    #[serde(alias = "calculated_mark", default)]
+    pub calculated_mark: String,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Assignments {
+    #[serde(rename(deserialize = "Assignment"), alias = "assignment", default)]
+    pub assignment: Vec<Assignment>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all(deserialize = "PascalCase"))]
+pub struct Assignment {
+    #[serde(rename(deserialize = "GradebookID"), alias = "id", default)]
+    pub id: String,
+    #[serde(rename(deserialize = "Measure"), alias = "name")]
+    pub name: String,
+    /// Category the assignment is graded under, matching an `AssignmentGradeCalc` name
+    #[serde(rename(deserialize = "Type"), alias = "category", default)]
+    pub category: String,
+    #[serde(alias = "date", default, deserialize_with = "de_util::optional")]
+    pub date: Option<Date>,
+    #[serde(alias = "due_date", default, deserialize_with = "de_util::optional")]
+    pub due_date: Option<Date>,
+    /// Score as shown to the student, e.g. "18 out of 20.0000" or "Not Graded"
+    #[serde(alias = "score", default)]
+    pub score: String,
+    #[serde(alias = "score_type", default)]
+    pub score_type: String,
+    /// Points as shown to the student, e.g. "18.00 / 20.0000" or "20.0000 Points Possible" when ungraded
+    #[serde(alias = "points", default)]
+    pub points: String,
+    #[serde(alias = "notes", default)]
+    pub notes: String,
+    #[serde(alias = "measure_description", default)]
+    pub measure_description: String,
+}
+
+impl Course {
+    /// Mark of the reporting period the gradebook was retrieved for
+    pub fn current_mark(&self) -> Option<&Mark> {
+        self.marks.iter().flat_map(|m| m.mark.iter()).next()
+    }
+}
+
+impl Mark {
+    /// Assignments graded under the mark
+    #[inline]
+    pub fn assignment_list(&self) -> impl Iterator<Item = &Assignment> {
+        self.assignments.assignment.iter()
+    }
+}
+
+impl Assignment {
+    /// Points earned, `None` if the assignment has not been graded
+    pub fn points_earned(&self) -> Option<f32> {
+        let (earned, _) = self.points.split_once('/')?;
+        earned.trim().parse().ok()
+    }
+
+    /// Points the assignment is worth
+    pub fn points_possible(&self) -> Option<f32> {
+        let possible = match self.points.split_once('/') {
+            Some((_, possible)) => possible,
+            None => self.points.trim().trim_end_matches("Points Possible"),
+        };
+
+        possible.trim().parse().ok()
+    }
+}
+
+/// Gradebooks of several reporting periods, ordered by period index
+#[derive(Debug, Default, Clone, Serialize, PartialEq)]
+pub struct MultiPeriodGradebook {
+    pub periods: Vec<PeriodGradebook>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct PeriodGradebook {
+    pub period: ReportPeriod,
+    pub gradebook: Gradebook,
+}
+
+impl MultiPeriodGradebook {
+    /// Percentage of a course in each reporting period, `None` for periods where the course is missing or ungraded
+    pub fn course_trend(&self, class_name: &str) -> Vec<(&ReportPeriod, Option<f32>)> {
+        self.periods
+            .iter()
+            .map(|p| {
+                let percent = p.gradebook.courses.course
+                    .iter()
+                    .find(|c| c.class_name == class_name)
+                    .and_then(Course::current_mark)
+                    .and_then(|m| m.percent);
+
+                (&p.period, percent)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quick_xml::de;
+
+    #[test]
+    fn serialize_snake_case() {
+        let xml = r#"<string><Gradebook><Courses>
+            <Course Period="1" Title="Chemistry" Room="204" Staff="Smith, J" StaffEMail="jsmith@school.test">
+                <Marks><Mark CalculatedScoreString="A" CalculatedScoreRaw="93.5" /></Marks>
+            </Course>
+            </Courses></Gradebook></string>"#;
+        let grades: GbData = de::from_str(xml).unwrap();
+        let json = serde_json::to_value(grades.clone()).unwrap();
+
+        assert_eq!(json["gradebook"]["courses"]["course"][0]["class_name"], "Chemistry");
+        assert_eq!(json["gradebook"]["courses"]["course"][0]["staff_email"], "jsmith@school.test");
+        assert_eq!(json["gradebook"]["courses"]["course"][0]["marks"][0]["mark"][0]["grade"], "A");
+    }
 }
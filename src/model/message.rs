@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+use super::{date::Date, de_util};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PXPMessagesData {
+    #[serde(rename(deserialize = "PXPMessagesData"), alias = "messages")]
+    pub messages: Messages,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all(deserialize = "PascalCase"))]
+pub struct Messages {
+    #[serde(alias = "message_listings", default)]
+    pub message_listings: MessageListings,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MessageListings {
+    #[serde(rename(deserialize = "MessageListing"), alias = "message", default)]
+    pub message: Vec<Message>,
+}
+
+/// Message sent to the student by staff or the district
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all(deserialize = "PascalCase"))]
+pub struct Message {
+    #[serde(rename(deserialize = "ID"), alias = "id", default)]
+    pub id: String,
+    #[serde(rename(deserialize = "Type"), alias = "message_type", default)]
+    pub message_type: String,
+    #[serde(alias = "begin_date", default, deserialize_with = "de_util::optional")]
+    pub begin_date: Option<Date>,
+    #[serde(alias = "title", default)]
+    pub title: String,
+    #[serde(alias = "subject", default)]
+    pub subject: String,
+    /// Body of the message, usually HTML
+    #[serde(alias = "content", default)]
+    pub content: String,
+    #[serde(alias = "from", default)]
+    pub from: String,
+    #[serde(alias = "read", default)]
+    pub read: bool,
+    #[serde(alias = "module", default)]
+    pub module: String,
+    #[serde(rename(deserialize = "AttachmentDatas"), alias = "attachments", default)]
+    pub attachments: Attachments,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Attachments {
+    #[serde(rename(deserialize = "AttachmentData"), alias = "attachment", default)]
+    pub attachment: Vec<Attachment>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all(deserialize = "PascalCase"))]
+pub struct Attachment {
+    #[serde(rename(deserialize = "AttachmentName"), alias = "name", default)]
+    pub name: String,
+    #[serde(rename(deserialize = "SmAttachmentGU"), alias = "attachment_gu", default)]
+    pub attachment_gu: String,
+}
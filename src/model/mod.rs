@@ -1,7 +1,14 @@
 //! Serde XML models for formatting incoming XML data
 
 pub mod attendance;
+pub mod calendar;
+pub mod date;
+pub mod device;
+pub mod district;
 pub mod grade;
+pub mod message;
 pub mod student;
 pub mod schedule;
-pub mod school;
\ No newline at end of file
+pub mod school;
+
+mod de_util;
\ No newline at end of file
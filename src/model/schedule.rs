@@ -1,31 +1,165 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use super::{
+    date::{Date, Time},
+    de_util,
+};
 
-#[derive(Debug, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct StudentClassSchedule {
-    #[serde(rename = "StudentClassSchedule")]
+    #[serde(rename(deserialize = "StudentClassSchedule"), alias = "schedule")]
     pub schedule: Schedule,
 }
 
-#[derive(Debug, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Schedule {
-    #[serde(rename = "TermIndex")]
+    #[serde(rename(deserialize = "TermIndex"), alias = "term")]
     pub term: u8,
-    #[serde(rename = "ClassLists")]
+    #[serde(rename(deserialize = "TermIndexName"), alias = "term_name", default)]
+    pub term_name: String,
+    #[serde(rename(deserialize = "ErrorMessage"), alias = "error_message", default)]
+    pub error_message: String,
+    /// Today's bell schedule, only present on days school is in session
+    #[serde(rename(deserialize = "TodayScheduleInfoData"), alias = "today", default)]
+    pub today: Option<TodayScheduleInfo>,
+    #[serde(rename(deserialize = "ClassLists"), alias = "class_list", default)]
     pub class_list: ClassList,
+    #[serde(rename(deserialize = "TermLists"), alias = "terms", default)]
+    pub terms: TermLists,
+    #[serde(rename(deserialize = "ConcurrentSchoolStudentClassSchedules"), alias = "concurrent_schools", default)]
+    pub concurrent_schools: ConcurrentSchoolSchedules,
 }
 
-#[derive(Debug, Deserialize, PartialEq)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ClassList {
-    #[serde(rename = "ClassListing")]
+    #[serde(rename(deserialize = "ClassListing"), alias = "class", default)]
     pub class: Vec<Class>,
 }
 
-#[derive(Debug, Deserialize, PartialEq)]
-#[serde(rename_all = "PascalCase")]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all(deserialize = "PascalCase"))]
 pub struct Class {
+    #[serde(alias = "period")]
     pub period: u8,
+    #[serde(alias = "course_title")]
     pub course_title: String,
-    pub room_name: String, // damn CAFE
+    /// Room the class meets in, which is not always a number (e.g. "CAFE" or "GYM-B")
+    #[serde(alias = "room_name", default)]
+    pub room_name: String,
+    #[serde(alias = "teacher", default)]
     pub teacher: String,
+    #[serde(alias = "teacher_email", default)]
     pub teacher_email: String,
-}
\ No newline at end of file
+    #[serde(rename(deserialize = "SectionGU"), alias = "section_gu", default)]
+    pub section_gu: String,
+    #[serde(rename(deserialize = "TeacherStaffGU"), alias = "teacher_staff_gu", default)]
+    pub teacher_staff_gu: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all(deserialize = "PascalCase"))]
+pub struct TodayScheduleInfo {
+    #[serde(alias = "date", default, deserialize_with = "de_util::optional")]
+    pub date: Option<Date>,
+    #[serde(rename(deserialize = "SchoolInfos"), alias = "schools", default)]
+    pub schools: TodaySchools,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TodaySchools {
+    #[serde(rename(deserialize = "SchoolInfo"), alias = "school", default)]
+    pub school: Vec<TodaySchool>,
+}
+
+/// Bell schedule of a school for the current day
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all(deserialize = "PascalCase"))]
+pub struct TodaySchool {
+    #[serde(alias = "school_name", default)]
+    pub school_name: String,
+    #[serde(rename(deserialize = "BellSchedName"), alias = "bell_schedule_name", default)]
+    pub bell_schedule_name: String,
+    #[serde(rename(deserialize = "BellSchedGU"), alias = "bell_schedule_gu", default)]
+    pub bell_schedule_gu: String,
+    #[serde(alias = "classes", default)]
+    pub classes: TodayClasses,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TodayClasses {
+    #[serde(rename(deserialize = "ClassInfo"), alias = "class", default)]
+    pub class: Vec<TodayClass>,
+}
+
+/// Class meeting today along with its bell times
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all(deserialize = "PascalCase"))]
+pub struct TodayClass {
+    #[serde(alias = "period")]
+    pub period: u8,
+    #[serde(alias = "class_name")]
+    pub class_name: String,
+    #[serde(alias = "start_time", default, deserialize_with = "de_util::optional")]
+    pub start_time: Option<Time>,
+    #[serde(alias = "end_time", default, deserialize_with = "de_util::optional")]
+    pub end_time: Option<Time>,
+    #[serde(alias = "room_name", default)]
+    pub room_name: String,
+    #[serde(alias = "teacher_name", default)]
+    pub teacher_name: String,
+    #[serde(alias = "teacher_email", default)]
+    pub teacher_email: String,
+    #[serde(rename(deserialize = "SectionGU"), alias = "section_gu", default)]
+    pub section_gu: String,
+    #[serde(rename(deserialize = "StaffGU"), alias = "staff_gu", default)]
+    pub staff_gu: String,
+    #[serde(alias = "attendance_code", default)]
+    pub attendance_code: String,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TermLists {
+    #[serde(rename(deserialize = "TermListing"), alias = "term", default)]
+    pub term: Vec<Term>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all(deserialize = "PascalCase"))]
+pub struct Term {
+    #[serde(rename(deserialize = "TermIndex"), alias = "index")]
+    pub index: u8,
+    #[serde(rename(deserialize = "TermCode"), alias = "code", default)]
+    pub code: String,
+    #[serde(rename(deserialize = "TermName"), alias = "name", default)]
+    pub name: String,
+    #[serde(alias = "begin_date", default, deserialize_with = "de_util::optional")]
+    pub begin_date: Option<Date>,
+    #[serde(alias = "end_date", default, deserialize_with = "de_util::optional")]
+    pub end_date: Option<Date>,
+    #[serde(rename(deserialize = "SchoolYearTrmCodeGU"), alias = "school_year_term_code_gu", default)]
+    pub school_year_term_code_gu: String,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ConcurrentSchoolSchedules {
+    #[serde(rename(deserialize = "ConcurrentSchoolStudentClassSchedule"), alias = "school", default)]
+    pub school: Vec<ConcurrentSchoolSchedule>,
+}
+
+/// Classes the student takes at another school during the same year
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all(deserialize = "PascalCase"))]
+pub struct ConcurrentSchoolSchedule {
+    #[serde(alias = "school_name", default)]
+    pub school_name: String,
+    #[serde(rename(deserialize = "ConcurrentSchOrgYearGU"), alias = "org_year_gu", default)]
+    pub org_year_gu: String,
+    #[serde(rename(deserialize = "ClassLists"), alias = "class_list", default)]
+    pub class_list: ClassList,
+}
+
+impl Schedule {
+    /// The term the schedule was requested for
+    pub fn current_term(&self) -> Option<&Term> {
+        self.terms.term.iter().find(|t| t.index == self.term)
+    }
+}
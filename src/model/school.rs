@@ -1,40 +1,50 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct StudentSchoolInfoListing {
-    #[serde(rename = "StudentSchoolInfoListing")]
+    #[serde(rename(deserialize = "StudentSchoolInfoListing"), alias = "school_info")]
     pub school_info: SchoolInfo,
 }
 
-#[derive(Debug, Deserialize, PartialEq)]
-#[serde(rename_all = "PascalCase")]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all(deserialize = "PascalCase"))]
 pub struct SchoolInfo {
-    #[serde(rename = "School")]
+    #[serde(rename(deserialize = "School"), alias = "school_name")]
     pub school_name: String,
+    #[serde(alias = "principal", default)]
     pub principal: String,
+    #[serde(alias = "school_address")]
     pub school_address: String,
+    #[serde(alias = "school_city")]
     pub school_city: String,
+    #[serde(alias = "school_state")]
     pub school_state: String,
-    pub school_zip: u32,
+    /// Five digit or ZIP+4 postal code
+    #[serde(alias = "school_zip")]
+    pub school_zip: String,
+    #[serde(alias = "phone", default)]
     pub phone: String,
-    #[serde(rename = "URL")]
+    #[serde(rename(deserialize = "URL"), alias = "url", default)]
     pub url: String,
-    #[serde(rename = "StaffLists")]
+    #[serde(rename(deserialize = "StaffLists"), alias = "staff_list", default)]
     pub staff_list: StaffList,
 }
 
-#[derive(Debug, Deserialize, PartialEq)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
 pub struct StaffList {
-    #[serde(rename = "StaffList")]
+    #[serde(rename(deserialize = "StaffList"), alias = "staff", default)]
     pub staff: Vec<Staff>,
 }
 
-#[derive(Debug, Deserialize, PartialEq)]
-#[serde(rename_all = "PascalCase")]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all(deserialize = "PascalCase"))]
 pub struct Staff {
+    #[serde(alias = "name")]
     pub name: String,
-    #[serde(rename = "EMail")]
+    #[serde(rename(deserialize = "EMail"), alias = "email", default)]
     pub email: String,
+    #[serde(alias = "title", default)]
     pub title: String,
+    #[serde(alias = "phone", default)]
     pub phone: String,
 }
\ No newline at end of file
@@ -1,25 +1,172 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use super::{date::Date, de_util};
 
-#[derive(Debug, Deserialize, PartialEq)]
-#[serde(rename_all = "PascalCase")]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all(deserialize = "PascalCase"))]
 pub struct StudentInfo {
-    #[serde(rename = "FormattedName")]
+    #[serde(rename(deserialize = "FormattedName"), alias = "name")]
     pub name: String,
-    #[serde(rename = "PermID")]
+    #[serde(rename(deserialize = "PermID"), alias = "id")]
     pub id: u32,
+    #[serde(alias = "gender")]
     pub gender: String,
+    #[serde(alias = "grade")]
     pub grade: u8,
+    #[serde(alias = "address", default)]
     pub address: String,
+    #[serde(rename(deserialize = "LastNameGoesBy"), alias = "last_name_goes_by", default)]
+    pub last_name_goes_by: Option<String>,
+    #[serde(rename(deserialize = "NickName"), alias = "nickname", default)]
     pub nickname: Option<String>,
-    pub birth_date: String,
-    #[serde(rename = "EMail")]
+    #[serde(alias = "birth_date", default, deserialize_with = "de_util::optional")]
+    pub birth_date: Option<Date>,
+    #[serde(rename(deserialize = "EMail"), alias = "email", default)]
     pub email: String,
+    #[serde(alias = "phone", default)]
     pub phone: String,
+    #[serde(alias = "home_language", default)]
+    pub home_language: String,
+    #[serde(alias = "current_school")]
     pub current_school: String,
+    #[serde(alias = "track", default)]
+    pub track: String,
+    #[serde(rename(deserialize = "HomeRoomTch"), alias = "home_room_teacher", default)]
+    pub home_room_teacher: String,
+    #[serde(rename(deserialize = "HomeRoomTchEMail"), alias = "home_room_teacher_email", default)]
+    pub home_room_teacher_email: String,
+    #[serde(rename(deserialize = "HomeRoomTchStaffGU"), alias = "home_room_teacher_staff_gu", default)]
+    pub home_room_teacher_staff_gu: String,
+    #[serde(alias = "home_room", default)]
+    pub home_room: String,
+    #[serde(rename(deserialize = "OrgYearGU"), alias = "org_year_gu", default)]
+    pub org_year_gu: String,
+    #[serde(alias = "counselor_name", default)]
+    pub counselor_name: String,
+    #[serde(alias = "counselor_email", default)]
+    pub counselor_email: String,
+    #[serde(rename(deserialize = "CounselorStaffGU"), alias = "counselor_staff_gu", default)]
+    pub counselor_staff_gu: String,
+    /// Decoded student photo, usually a JPEG
+    #[serde(alias = "photo", default, with = "de_util::base64_bytes")]
+    pub photo: Option<Vec<u8>>,
+    #[serde(rename(deserialize = "LockerInfoRecords"), alias = "lockers", default)]
+    pub lockers: LockerInfoRecords,
+    #[serde(alias = "emergency_contacts", default)]
+    pub emergency_contacts: EmergencyContacts,
+    #[serde(alias = "physician", default)]
+    pub physician: Option<Physician>,
+    #[serde(alias = "dentist", default)]
+    pub dentist: Option<Dentist>,
+    #[serde(rename(deserialize = "UserDefinedGroupBoxes"), alias = "user_defined_groups", default)]
+    pub user_defined_groups: UserDefinedGroupBoxes,
 }
 
-#[derive(Debug, Deserialize, PartialEq)]
-#[serde(rename_all = "PascalCase")]
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LockerInfoRecords {
+    #[serde(rename(deserialize = "StudentLockerInfoRecord"), alias = "locker", default)]
+    pub locker: Vec<Locker>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all(deserialize = "PascalCase"))]
+pub struct Locker {
+    #[serde(rename(deserialize = "LockerGU"), alias = "locker_gu", default)]
+    pub locker_gu: String,
+    #[serde(alias = "locker_number", default)]
+    pub locker_number: String,
+    #[serde(alias = "current_combination", default)]
+    pub current_combination: String,
+    #[serde(alias = "location", default)]
+    pub location: String,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EmergencyContacts {
+    #[serde(rename(deserialize = "EmergencyContact"), alias = "contact", default)]
+    pub contact: Vec<EmergencyContact>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all(deserialize = "PascalCase"))]
+pub struct EmergencyContact {
+    #[serde(alias = "name")]
+    pub name: String,
+    #[serde(alias = "relationship", default)]
+    pub relationship: String,
+    #[serde(alias = "home_phone", default)]
+    pub home_phone: String,
+    #[serde(alias = "work_phone", default)]
+    pub work_phone: String,
+    #[serde(alias = "other_phone", default)]
+    pub other_phone: String,
+    #[serde(alias = "mobile_phone", default)]
+    pub mobile_phone: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all(deserialize = "PascalCase"))]
+pub struct Physician {
+    #[serde(alias = "name", default)]
+    pub name: String,
+    #[serde(alias = "hospital", default)]
+    pub hospital: String,
+    #[serde(alias = "phone", default)]
+    pub phone: String,
+    #[serde(rename(deserialize = "Extn"), alias = "extension", default)]
+    pub extension: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all(deserialize = "PascalCase"))]
+pub struct Dentist {
+    #[serde(alias = "name", default)]
+    pub name: String,
+    #[serde(alias = "office", default)]
+    pub office: String,
+    #[serde(alias = "phone", default)]
+    pub phone: String,
+    #[serde(rename(deserialize = "Extn"), alias = "extension", default)]
+    pub extension: String,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UserDefinedGroupBoxes {
+    #[serde(rename(deserialize = "UserDefinedGroupBox"), alias = "group", default)]
+    pub group: Vec<UserDefinedGroupBox>,
+}
+
+/// District specific group of fields, such as bus routes or graduation requirements
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all(deserialize = "PascalCase"))]
+pub struct UserDefinedGroupBox {
+    #[serde(rename(deserialize = "GroupBoxLabel"), alias = "label", default)]
+    pub label: String,
+    #[serde(rename(deserialize = "GroupBoxID"), alias = "id", default)]
+    pub id: String,
+    #[serde(rename(deserialize = "UserDefinedItems"), alias = "items", default)]
+    pub items: UserDefinedItems,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UserDefinedItems {
+    #[serde(rename(deserialize = "UserDefinedItem"), alias = "item", default)]
+    pub item: Vec<UserDefinedItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all(deserialize = "PascalCase"))]
+pub struct UserDefinedItem {
+    #[serde(rename(deserialize = "ItemLabel"), alias = "label", default)]
+    pub label: String,
+    #[serde(rename(deserialize = "ItemType"), alias = "item_type", default)]
+    pub item_type: String,
+    #[serde(alias = "value", default)]
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all(deserialize = "PascalCase"))]
 pub struct Student {
+    #[serde(alias = "student_info")]
     pub student_info: StudentInfo,
-}
\ No newline at end of file
+}
@@ -1,9 +1,15 @@
 //! HTTP client wrapper
 //!
-//! This module contains `WebHandle` which is a simple wrapper over [Client](https://docs.rs/reqwest/0.10.1/reqwest/struct.Client.html)
-//! used to communicate with a web server without needing numerous parameters
+//! This module contains `WebHandle` which is a simple wrapper over [Client](https://docs.rs/reqwest/0.12/reqwest/struct.Client.html)
+//! used to communicate with a web server without needing numerous parameters, along with the `Transport` trait
+//! which lets `Client` send its requests through any HTTP stack
 
-use std::ops::Deref;
+use crate::error::VueError;
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+};
 use reqwest::{
     Client,
     Method,
@@ -24,16 +30,90 @@ lazy_static! {
     };
 }
 
+/// Boxed future which transports can use as their `Transport::Future`
+pub type TransportFuture<'a> = Pin<Box<dyn Future<Output = Result<String, VueError>> + Send + 'a>>;
+
+/// Boxed future for transports which cannot be sent between threads, such as on single threaded or WASM runtimes
+pub type LocalTransportFuture<'a> = Pin<Box<dyn Future<Output = Result<String, VueError>> + 'a>>;
+
+/// HTTP stack used by `Client` to reach the StudentVUE service
+///
+/// `WebHandle` is the default reqwest based implementation, other implementations can be supplied through
+/// `Client::create_with_transport` to record requests, audit traffic or run on alternative runtimes. The
+/// futures of a `Client` are `Send` when those of its transport are, transports whose futures are not, e.g.
+/// on WASM, can use `LocalTransportFuture`.
+///
+/// # Example
+///
+/// ```
+/// use studentvue::{
+///     client::Client,
+///     error::VueError,
+///     request::{Transport, TransportFuture},
+/// };
+///
+/// struct Canned;
+///
+/// impl Transport for Canned {
+///     type Future<'a> = TransportFuture<'a>;
+///
+///     fn post_form<'a>(&'a self, _uri: &'a str, _params: &'a [(&'a str, &'a str)]) -> TransportFuture<'a> {
+///         Box::pin(async { Ok(String::from("<string />")) })
+///     }
+/// }
+///
+/// let client = Client::create_with_transport("https://studentvue.phoenixunion.org", "user", "pwd", Canned);
+/// ```
+pub trait Transport {
+    /// Future resolving to the response body
+    type Future<'a>: Future<Output = Result<String, VueError>> + 'a
+    where
+        Self: 'a;
+
+    /// Sends a form encoded POST request to `uri`, resolving to the raw response body; `Client` decodes the
+    /// escaped XML payload
+    fn post_form<'a>(&'a self, uri: &'a str, params: &'a [(&'a str, &'a str)]) -> Self::Future<'a>;
+}
+
+/// Lets several clients, e.g. of different accounts, share one transport
+impl<T: Transport> Transport for Arc<T> {
+    type Future<'a> = T::Future<'a> where Self: 'a;
+
+    fn post_form<'a>(&'a self, uri: &'a str, params: &'a [(&'a str, &'a str)]) -> Self::Future<'a> {
+        (**self).post_form(uri, params)
+    }
+}
+
 /// Struct which manages and sends web requests asynchronously
-#[derive(Clone, Copy)]
-pub struct WebHandle;
+///
+/// A `WebHandle` keeps a connection pool, which its clones share, so clients created over clones of one
+/// handle reuse connections to their district.
+#[derive(Debug, Default, Clone)]
+pub struct WebHandle {
+    client: Client,
+}
+
+impl Transport for WebHandle {
+    type Future<'a> = TransportFuture<'a>;
+
+    fn post_form<'a>(&'a self, uri: &'a str, params: &'a [(&'a str, &'a str)]) -> TransportFuture<'a> {
+        Box::pin(async move {
+            Ok(self.post(uri, params).await?)
+        })
+    }
+}
 
 impl WebHandle {
-    /// Asynchronously sends a HTTP Request requiring manual parameters which returns a [Response](https://docs.rs/reqwest/0.10.1/reqwest/struct.Response.html)
+    /// Creates a handle with its own connection pool
+    pub fn new() -> Self {
+        WebHandle::default()
+    }
+
+    /// Asynchronously sends a HTTP Request requiring manual parameters which returns a [Response](https://docs.rs/reqwest/0.12/reqwest/struct.Response.html)
     ///
     /// # Example
     ///
-    /// ```
+    /// ```no_run
     /// use studentvue::request::WebHandle;
     /// use reqwest::{
     ///     header::HeaderMap,
@@ -69,11 +149,12 @@ impl WebHandle {
         Ok(request)
     }
 
-    /// Asynchronously sends a POST request to the corresponding WebService endpoint or an optional url with specified parameters
+    /// Asynchronously sends a POST request to the corresponding WebService endpoint or an optional url with specified parameters,
+    /// failing on error statuses
     ///
     /// # Example
     ///
-    /// ```
+    /// ```no_run
     /// use studentvue::request::WebHandle;
     ///
     /// #[tokio::main]
@@ -86,30 +167,83 @@ impl WebHandle {
     /// ```
     ///
     pub async fn send(uri: impl AsRef<str>, params: impl serde::Serialize) -> Result<String, reqwest::Error> {
-        let req = WebHandle::make_web_request(uri, Method::POST, params, DEFAULT_HEADERS.deref())
+        Ok(unescape(&WebHandle::new().post(uri, params).await?))
+    }
+
+    /// Sends a POST request over the connection pool of this handle, failing on error statuses and returning
+    /// the body as is
+    pub async fn post(&self, uri: impl AsRef<str>, params: impl serde::Serialize) -> Result<String, reqwest::Error> {
+        let req = self.client
+            .post(uri.as_ref())
+            .headers(DEFAULT_HEADERS.clone())
+            .form(&params)
+            .send()
             .await?
+            .error_for_status()?
             .text()
             .await?;
 
-        Ok(
-            String::from_utf8_lossy(req.as_bytes())
-                .replace("&lt;", "<")
-                .replace("&gt;", ">")
-        )
+        Ok(req)
     }
 }
 
+/// Decodes the XML payload which the service escapes inside its `<string>` envelope
+pub(crate) fn unescape(body: &str) -> String {
+    body.replace("&lt;", "<").replace("&gt;", ">")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    // Serves a single canned HTTP response on a local port
+    async fn serve_once(response: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 4096];
+            let _ = stream.read(&mut buf).await.unwrap();
+            stream.write_all(response.as_bytes()).await.unwrap();
+        });
+
+        format!("http://{}", addr)
+    }
+
     #[tokio::test]
     async fn status_check() {
-        let status = WebHandle::make_web_request("http://www.google.com", Method::GET, <Vec<&str>>::new(), &HeaderMap::new())
+        let uri = serve_once("HTTP/1.1 200 OK\r\ncontent-length: 0\r\nconnection: close\r\n\r\n").await;
+        let status = WebHandle::make_web_request(uri, Method::GET, <Vec<&str>>::new(), &HeaderMap::new())
             .await
             .unwrap()
             .status();
 
         assert_eq!(status.as_str(), "200");
     }
+
+    #[tokio::test]
+    async fn send_unescapes_body() {
+        let uri = serve_once("HTTP/1.1 200 OK\r\ncontent-length: 36\r\nconnection: close\r\n\r\n<string>&lt;Gradebook /&gt;</string>").await;
+        let body = WebHandle::send(uri, &[("key", "value")])
+            .await
+            .unwrap();
+
+        assert_eq!(body, "<string><Gradebook /></string>");
+    }
+
+    #[tokio::test]
+    async fn send_rejects_server_errors() {
+        let uri = serve_once("HTTP/1.1 503 Service Unavailable\r\ncontent-length: 0\r\nconnection: close\r\n\r\n").await;
+        let err = WebHandle::send(uri, &[("key", "value")])
+            .await
+            .unwrap_err();
+
+        assert_eq!(err.status().map(|s| s.as_u16()), Some(503));
+    }
 }
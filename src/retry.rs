@@ -0,0 +1,207 @@
+//! Retrying of transient failures
+//!
+//! This module provides `RetryPolicy`, which `Client::with_retry` uses to resend service calls failing with
+//! timeouts, connection errors or server errors. Waits between attempts grow exponentially with a random
+//! jitter, and credential errors are never retried. Only methods which can safely be sent twice are retried,
+//! by default every method except those updating data on the server such as `UpdatePXPMessage`.
+
+use crate::{enums::Method, error::VueError};
+use std::{
+    collections::hash_map::RandomState,
+    fmt,
+    hash::{BuildHasher, Hasher},
+    sync::Arc,
+    time::Duration,
+};
+
+/// Decides whether and when a failed service call is sent again
+///
+/// # Example
+///
+/// ```
+/// use studentvue::retry::RetryPolicy;
+/// use std::time::Duration;
+///
+/// let policy = RetryPolicy::new(4)
+///     .with_backoff(Duration::from_millis(500), Duration::from_secs(10))
+///     .with_jitter(0.2);
+///
+/// assert_eq!(policy.max_attempts(), 4);
+/// ```
+#[derive(Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    multiplier: f64,
+    jitter: f64,
+    methods: Arc<[String]>,
+    retryable: Arc<dyn Fn(&VueError) -> bool + Send + Sync>,
+}
+
+/// Methods changing data on the server, which are not retried by default
+const NON_IDEMPOTENT: [Method; 6] = [
+    Method::SaveSoundFileData,
+    Method::UploadGBDocDataForStudentAssignment,
+    Method::UpdateStudentHWNotes,
+    Method::UpdatePXPMessage,
+    Method::UpdateDeviceToken,
+    Method::GenerateAuthToken,
+];
+
+impl fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_attempts", &self.max_attempts)
+            .field("initial_backoff", &self.initial_backoff)
+            .field("max_backoff", &self.max_backoff)
+            .field("multiplier", &self.multiplier)
+            .field("jitter", &self.jitter)
+            .field("methods", &self.methods)
+            .finish()
+    }
+}
+
+impl RetryPolicy {
+    /// Policy making up to `max_attempts` attempts, waiting 250ms after the first failure and doubling the
+    /// wait up to 30s with a jitter of 10%
+    pub fn new(max_attempts: u32) -> Self {
+        RetryPolicy {
+            max_attempts: max_attempts.max(1),
+            initial_backoff: Duration::from_millis(250),
+            max_backoff: Duration::from_secs(30),
+            multiplier: 2.0,
+            jitter: 0.1,
+            methods: Method::ALL.iter()
+                .filter(|method| !NON_IDEMPOTENT.contains(method))
+                .map(|&method| <&str>::from(method).to_owned())
+                .collect(),
+            retryable: Arc::new(is_transient),
+        }
+    }
+
+    /// Policy which never retries
+    pub fn never() -> Self {
+        RetryPolicy::new(1)
+    }
+
+    /// Waits `initial` after the first failure, never waiting longer than `max`
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    /// Factor the wait grows by after every failed attempt
+    pub fn with_multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier.max(1.0);
+        self
+    }
+
+    /// Randomly varies every wait by up to `fraction` of it in either direction
+    pub fn with_jitter(mut self, fraction: f64) -> Self {
+        self.jitter = fraction.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Replaces the names of the methods which are retried, calls of any other method are sent only once
+    pub fn with_methods<S: AsRef<str>>(mut self, methods: impl IntoIterator<Item = S>) -> Self {
+        self.methods = methods.into_iter().map(|m| m.as_ref().to_owned()).collect();
+        self
+    }
+
+    /// Replaces the check deciding which errors are retried, credential errors are never retried regardless
+    pub fn retry_if(mut self, retryable: impl Fn(&VueError) -> bool + Send + Sync + 'static) -> Self {
+        self.retryable = Arc::new(retryable);
+        self
+    }
+
+    #[inline]
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// Whether a call failing with `err` may be sent again
+    pub fn is_retryable(&self, err: &VueError) -> bool {
+        !err.is_credentials() && (self.retryable)(err)
+    }
+
+    /// Whether calls of the method named `method` may be sent again, names are compared case-insensitively
+    pub fn retries_method(&self, method: &str) -> bool {
+        self.methods.iter().any(|m| m.eq_ignore_ascii_case(method))
+    }
+
+    /// Wait before the attempt following failed attempt number `attempt`, counting from 1
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let wait = self.initial_backoff.as_secs_f64() * self.multiplier.powi(exponent);
+        let wait = wait.min(self.max_backoff.as_secs_f64());
+        let jitter = wait * self.jitter * (random_fraction() * 2.0 - 1.0);
+
+        Duration::from_secs_f64((wait + jitter).max(0.0))
+    }
+}
+
+impl Default for RetryPolicy {
+    /// Three attempts with the backoff of `RetryPolicy::new`
+    fn default() -> Self {
+        RetryPolicy::new(3)
+    }
+}
+
+/// Default retry check, accepting timeouts, connection and I/O failures along with 429 and 5xx responses
+pub fn is_transient(err: &VueError) -> bool {
+    match err {
+        VueError::Request(e) => {
+            e.is_timeout() || e.is_connect() || e.is_request() || e.is_body()
+                || e.status().is_some_and(|s| s.is_server_error() || s.as_u16() == 429)
+        }
+        VueError::Io(_) => true,
+        _ => false,
+    }
+}
+
+/// Random number in `[0, 1)`
+pub(crate) fn random_fraction() -> f64 {
+    let seed = RandomState::new().build_hasher().finish();
+    (seed >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_growth() {
+        let policy = RetryPolicy::new(5)
+            .with_backoff(Duration::from_secs(1), Duration::from_secs(5))
+            .with_jitter(0.0);
+
+        let waits: Vec<_> = (1..=4).map(|attempt| policy.backoff(attempt).as_secs()).collect();
+        assert_eq!(waits, [1, 2, 4, 5]);
+
+        let jittered = RetryPolicy::new(5).with_backoff(Duration::from_secs(1), Duration::from_secs(5)).with_jitter(0.5);
+        assert!((0.5..=1.5).contains(&jittered.backoff(1).as_secs_f64()));
+    }
+
+    #[test]
+    fn credentials_never_retried() {
+        let policy = RetryPolicy::new(3).retry_if(|_| true);
+
+        assert!(policy.is_retryable(&VueError::Service(String::from("Server busy"))));
+        assert!(!policy.is_retryable(&VueError::from_service_message("Invalid user id or password")));
+        assert!(!RetryPolicy::default().is_retryable(&VueError::Service(String::from("Server busy"))));
+    }
+
+    #[test]
+    fn idempotent_methods() {
+        let policy = RetryPolicy::default();
+        assert!(policy.retries_method("Gradebook"));
+        assert!(!policy.retries_method("UpdatePXPMessage"));
+        assert!(!policy.retries_method("GetNewMethod"));
+
+        let policy = policy.with_methods(["GetNewMethod"]);
+        assert!(policy.retries_method("getnewmethod"));
+        assert!(!policy.retries_method("GradeBook"));
+    }
+}
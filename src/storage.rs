@@ -0,0 +1,564 @@
+//! Local persistence of fetched data in SQLite
+//!
+//! This module provides `Store`, which writes gradebooks, assignments, attendance, schedules and messages into
+//! a SQLite database. It is enabled with the `sqlite` feature.
+//!
+//! Re-syncing the same data updates rows in place, while changes to marks and assignment scores are appended
+//! to history tables. Rows which disappear from the district are kept, with `last_seen` recording the last
+//! sync which returned them, so past grades remain queryable after the district purges them.
+//!
+//! Rows are stored per `StudentKey`, the district url and user id, so students of different districts sharing
+//! a user id are kept apart. A `StudentKey` is taken from a `&Client` or a `(district_url, user)` pair.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use studentvue::{client::Client, storage::Store};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let client = Client::create("https://studentvue.phoenixunion.org", "user", "pwd");
+//!     let mut store = Store::open("studentvue.db").unwrap();
+//!
+//!     let grades = client.get_grades(None).await.unwrap();
+//!     store.save_gradebook(&client, &grades.gradebook).unwrap();
+//!
+//!     for mark in store.mark_history(&client, "AP Chemistry").unwrap() {
+//!         println!("{} {} {:?}", mark.period_name, mark.grade, mark.percent);
+//!     }
+//! }
+//! ```
+
+use crate::{
+    client::Client,
+    error::VueError,
+    model::{
+        attendance::AttData,
+        date::Date,
+        grade::{Assignment, Gradebook, MultiPeriodGradebook},
+        message::PXPMessagesData,
+        schedule::StudentClassSchedule,
+    },
+    request::Transport,
+};
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use std::{
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Schema migrations, the schema version is the number of migrations applied
+const MIGRATIONS: &[&str] = &[
+    "CREATE TABLE courses (
+        district_url TEXT NOT NULL,
+        user TEXT NOT NULL,
+        period_name TEXT NOT NULL,
+        class_name TEXT NOT NULL,
+        period INTEGER NOT NULL,
+        room TEXT NOT NULL,
+        staff TEXT NOT NULL,
+        staff_email TEXT NOT NULL,
+        first_seen INTEGER NOT NULL,
+        last_seen INTEGER NOT NULL,
+        PRIMARY KEY (district_url, user, period_name, class_name)
+    );
+    CREATE TABLE mark_history (
+        id INTEGER PRIMARY KEY,
+        district_url TEXT NOT NULL,
+        user TEXT NOT NULL,
+        period_name TEXT NOT NULL,
+        class_name TEXT NOT NULL,
+        grade TEXT NOT NULL,
+        percent REAL,
+        recorded_at INTEGER NOT NULL
+    );
+    CREATE TABLE assignments (
+        district_url TEXT NOT NULL,
+        user TEXT NOT NULL,
+        period_name TEXT NOT NULL,
+        class_name TEXT NOT NULL,
+        assignment_key TEXT NOT NULL,
+        name TEXT NOT NULL,
+        category TEXT NOT NULL,
+        date TEXT,
+        due_date TEXT,
+        score TEXT NOT NULL,
+        points TEXT NOT NULL,
+        notes TEXT NOT NULL,
+        first_seen INTEGER NOT NULL,
+        last_seen INTEGER NOT NULL,
+        PRIMARY KEY (district_url, user, period_name, class_name, assignment_key)
+    );
+    CREATE TABLE score_history (
+        id INTEGER PRIMARY KEY,
+        district_url TEXT NOT NULL,
+        user TEXT NOT NULL,
+        period_name TEXT NOT NULL,
+        class_name TEXT NOT NULL,
+        assignment_key TEXT NOT NULL,
+        score TEXT NOT NULL,
+        points TEXT NOT NULL,
+        recorded_at INTEGER NOT NULL
+    );
+    CREATE TABLE absences (
+        district_url TEXT NOT NULL,
+        user TEXT NOT NULL,
+        date TEXT NOT NULL,
+        reason TEXT NOT NULL,
+        note TEXT NOT NULL,
+        periods TEXT NOT NULL,
+        first_seen INTEGER NOT NULL,
+        last_seen INTEGER NOT NULL,
+        PRIMARY KEY (district_url, user, date)
+    );
+    CREATE TABLE classes (
+        district_url TEXT NOT NULL,
+        user TEXT NOT NULL,
+        term_name TEXT NOT NULL,
+        period INTEGER NOT NULL,
+        course_title TEXT NOT NULL,
+        room_name TEXT NOT NULL,
+        teacher TEXT NOT NULL,
+        teacher_email TEXT NOT NULL,
+        section_gu TEXT NOT NULL,
+        first_seen INTEGER NOT NULL,
+        last_seen INTEGER NOT NULL,
+        PRIMARY KEY (district_url, user, term_name, period, course_title)
+    );
+    CREATE TABLE messages (
+        district_url TEXT NOT NULL,
+        user TEXT NOT NULL,
+        id TEXT NOT NULL,
+        begin_date TEXT,
+        sender TEXT NOT NULL,
+        subject TEXT NOT NULL,
+        content TEXT NOT NULL,
+        read INTEGER NOT NULL,
+        first_seen INTEGER NOT NULL,
+        last_seen INTEGER NOT NULL,
+        PRIMARY KEY (district_url, user, id)
+    );",
+];
+
+/// Recorded mark of a course
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarkRecord {
+    pub period_name: String,
+    pub grade: String,
+    pub percent: Option<f32>,
+    /// Unix time of the sync which recorded the mark
+    pub recorded_at: i64,
+}
+
+/// Recorded score of an assignment
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScoreRecord {
+    pub score: String,
+    pub points: String,
+    /// Unix time of the sync which recorded the score
+    pub recorded_at: i64,
+}
+
+/// District and user which stored rows belong to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StudentKey<'a> {
+    pub district_url: &'a str,
+    pub user: &'a str,
+}
+
+impl<'a> StudentKey<'a> {
+    /// Creates a key, `district_url` is stored without trailing slashes like `Client::uri`
+    pub fn new(district_url: &'a str, user: &'a str) -> Self {
+        StudentKey { district_url: district_url.trim_end_matches('/'), user }
+    }
+}
+
+impl<'a, T: Transport> From<&'a Client<T>> for StudentKey<'a> {
+    fn from(client: &'a Client<T>) -> Self {
+        StudentKey::new(&client.uri, client.user())
+    }
+}
+
+impl<'a> From<(&'a str, &'a str)> for StudentKey<'a> {
+    fn from((district_url, user): (&'a str, &'a str)) -> Self {
+        StudentKey::new(district_url, user)
+    }
+}
+
+/// SQLite database holding fetched data of any number of students
+#[derive(Debug)]
+pub struct Store {
+    conn: Connection,
+}
+
+impl Store {
+    /// Opens or creates the database at `path`, migrating it to the current schema
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, VueError> {
+        Store::from_connection(Connection::open(path)?)
+    }
+
+    /// Creates a database living in memory
+    pub fn open_in_memory() -> Result<Self, VueError> {
+        Store::from_connection(Connection::open_in_memory()?)
+    }
+
+    fn from_connection(mut conn: Connection) -> Result<Self, VueError> {
+        let version: usize = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        if version > MIGRATIONS.len() {
+            return Err(VueError::Storage(
+                format!("database schema version {} is newer than the supported version {}", version, MIGRATIONS.len()).into(),
+            ));
+        }
+
+        let tx = conn.transaction()?;
+        for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+            tx.execute_batch(migration)?;
+            tx.pragma_update(None, "user_version", index + 1)?;
+        }
+        tx.commit()?;
+
+        Ok(Store { conn })
+    }
+
+    /// Version of the database schema
+    pub fn schema_version(&self) -> Result<usize, VueError> {
+        Ok(self.conn.query_row("PRAGMA user_version", [], |row| row.get(0))?)
+    }
+
+    /// Saves the courses, marks and assignments of a gradebook
+    pub fn save_gradebook<'a>(&mut self, student: impl Into<StudentKey<'a>>, gradebook: &Gradebook) -> Result<(), VueError> {
+        let period_name = gradebook.reporting_period.as_ref().map(|p| p.name.as_str());
+
+        let tx = self.conn.transaction()?;
+        write_gradebook(&tx, student.into(), period_name, gradebook, now())?;
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// Saves the gradebook of every reporting period
+    pub fn save_all_grades<'a>(&mut self, student: impl Into<StudentKey<'a>>, grades: &MultiPeriodGradebook) -> Result<(), VueError> {
+        let student = student.into();
+        let now = now();
+
+        let tx = self.conn.transaction()?;
+        for period in grades.periods.iter() {
+            write_gradebook(&tx, student, Some(&period.period.name), &period.gradebook, now)?;
+        }
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// Saves the absences of the student
+    pub fn save_attendance<'a>(&mut self, student: impl Into<StudentKey<'a>>, attendance: &AttData) -> Result<(), VueError> {
+        let student = student.into();
+        let now = now();
+
+        let tx = self.conn.transaction()?;
+        for absence in attendance.attendance.absences.absence.iter() {
+            let periods: Vec<String> = absence.periods.period.iter().map(|p| p.number.to_string()).collect();
+
+            tx.execute(
+                "INSERT INTO absences VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?7)
+                ON CONFLICT (district_url, user, date) DO UPDATE SET
+                    reason = excluded.reason, note = excluded.note, periods = excluded.periods, last_seen = excluded.last_seen",
+                params![student.district_url, student.user, absence.absence_date.to_iso_string(), absence.reason, absence.note, periods.join(","), now],
+            )?;
+        }
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// Saves the classes of a schedule under its term name
+    pub fn save_schedule<'a>(&mut self, student: impl Into<StudentKey<'a>>, schedule: &StudentClassSchedule) -> Result<(), VueError> {
+        let student = student.into();
+        let now = now();
+        let schedule = &schedule.schedule;
+
+        let tx = self.conn.transaction()?;
+        for class in schedule.class_list.class.iter() {
+            tx.execute(
+                "INSERT INTO classes VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?10)
+                ON CONFLICT (district_url, user, term_name, period, course_title) DO UPDATE SET
+                    room_name = excluded.room_name, teacher = excluded.teacher, teacher_email = excluded.teacher_email,
+                    section_gu = excluded.section_gu, last_seen = excluded.last_seen",
+                params![student.district_url, student.user, schedule.term_name, class.period, class.course_title, class.room_name, class.teacher,
+                    class.teacher_email, class.section_gu, now],
+            )?;
+        }
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// Saves the messages sent to the student
+    pub fn save_messages<'a>(&mut self, student: impl Into<StudentKey<'a>>, messages: &PXPMessagesData) -> Result<(), VueError> {
+        let student = student.into();
+        let now = now();
+
+        let tx = self.conn.transaction()?;
+        for message in messages.messages.message_listings.message.iter() {
+            tx.execute(
+                "INSERT INTO messages VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?9)
+                ON CONFLICT (district_url, user, id) DO UPDATE SET
+                    subject = excluded.subject, content = excluded.content, read = excluded.read, last_seen = excluded.last_seen",
+                params![student.district_url, student.user, message.id, message.begin_date.map(|d| d.to_iso_string()), message.from, message.subject,
+                    message.content, message.read, now],
+            )?;
+        }
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// Every recorded mark of a course, oldest first
+    pub fn mark_history<'a>(&self, student: impl Into<StudentKey<'a>>, class_name: &str) -> Result<Vec<MarkRecord>, VueError> {
+        let student = student.into();
+        let mut stmt = self.conn.prepare(
+            "SELECT period_name, grade, percent, recorded_at FROM mark_history
+            WHERE district_url = ?1 AND user = ?2 AND class_name = ?3 ORDER BY id",
+        )?;
+        let records = stmt.query_map(params![student.district_url, student.user, class_name], |row| {
+            Ok(MarkRecord {
+                period_name: row.get(0)?,
+                grade: row.get(1)?,
+                percent: row.get::<_, Option<f64>>(2)?.map(|p| p as f32),
+                recorded_at: row.get(3)?,
+            })
+        })?;
+
+        Ok(records.collect::<Result<_, _>>()?)
+    }
+
+    /// Every recorded score of an assignment, oldest first; assignments are identified by their gradebook
+    /// id, or by their name when the district sends no id
+    pub fn score_history<'a>(&self, student: impl Into<StudentKey<'a>>, class_name: &str, assignment: &str) -> Result<Vec<ScoreRecord>, VueError> {
+        let student = student.into();
+        let mut stmt = self.conn.prepare(
+            "SELECT score, points, recorded_at FROM score_history
+            WHERE district_url = ?1 AND user = ?2 AND class_name = ?3 AND assignment_key = ?4 ORDER BY id",
+        )?;
+        let records = stmt.query_map(params![student.district_url, student.user, class_name, assignment], |row| {
+            Ok(ScoreRecord { score: row.get(0)?, points: row.get(1)?, recorded_at: row.get(2)? })
+        })?;
+
+        Ok(records.collect::<Result<_, _>>()?)
+    }
+
+    /// Names of every course stored for a student, including courses no longer returned by the district
+    pub fn courses<'a>(&self, student: impl Into<StudentKey<'a>>) -> Result<Vec<String>, VueError> {
+        let student = student.into();
+        let mut stmt = self.conn.prepare(
+            "SELECT DISTINCT class_name FROM courses WHERE district_url = ?1 AND user = ?2 ORDER BY period, class_name",
+        )?;
+        let names = stmt.query_map(params![student.district_url, student.user], |row| row.get(0))?;
+
+        Ok(names.collect::<Result<_, _>>()?)
+    }
+
+    /// Dates of every stored absence of a student
+    pub fn absence_dates<'a>(&self, student: impl Into<StudentKey<'a>>) -> Result<Vec<Date>, VueError> {
+        let student = student.into();
+        let mut stmt = self.conn.prepare(
+            "SELECT date FROM absences WHERE district_url = ?1 AND user = ?2 ORDER BY date",
+        )?;
+        let dates = stmt.query_map(params![student.district_url, student.user], |row| row.get::<_, String>(0))?
+            .filter_map(|date| date.map(|d| d.parse().ok()).transpose());
+
+        Ok(dates.collect::<Result<_, _>>()?)
+    }
+
+    /// The underlying connection, for queries not covered by `Store`
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+}
+
+fn write_gradebook(tx: &Transaction, student: StudentKey, period_name: Option<&str>, gradebook: &Gradebook, now: i64) -> Result<(), VueError> {
+    for course in gradebook.courses.course.iter() {
+        let mark = course.current_mark();
+        let period_name = period_name.or(mark.map(|m| m.name.as_str())).unwrap_or_default();
+
+        tx.execute(
+            "INSERT INTO courses VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?9)
+            ON CONFLICT (district_url, user, period_name, class_name) DO UPDATE SET
+                period = excluded.period, room = excluded.room, staff = excluded.staff,
+                staff_email = excluded.staff_email, last_seen = excluded.last_seen",
+            params![student.district_url, student.user, period_name, course.class_name, course.period, course.room, course.staff, course.staff_email, now],
+        )?;
+
+        let mark = match mark {
+            Some(mark) => mark,
+            None => continue,
+        };
+
+        let latest: Option<(String, Option<f64>)> = tx.query_row(
+            "SELECT grade, percent FROM mark_history
+            WHERE district_url = ?1 AND user = ?2 AND period_name = ?3 AND class_name = ?4 ORDER BY id DESC LIMIT 1",
+            params![student.district_url, student.user, period_name, course.class_name],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        ).optional()?;
+
+        let percent = mark.percent.map(f64::from);
+        if latest != Some((mark.grade.clone(), percent)) {
+            tx.execute(
+                "INSERT INTO mark_history (district_url, user, period_name, class_name, grade, percent, recorded_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![student.district_url, student.user, period_name, course.class_name, mark.grade, percent, now],
+            )?;
+        }
+
+        for assignment in mark.assignment_list() {
+            write_assignment(tx, student, period_name, &course.class_name, assignment, now)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn write_assignment(tx: &Transaction, student: StudentKey, period_name: &str, class_name: &str, assignment: &Assignment, now: i64) -> Result<(), VueError> {
+    let key = if assignment.id.is_empty() { &assignment.name } else { &assignment.id };
+
+    tx.execute(
+        "INSERT INTO assignments VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?13)
+        ON CONFLICT (district_url, user, period_name, class_name, assignment_key) DO UPDATE SET
+            name = excluded.name, category = excluded.category, date = excluded.date, due_date = excluded.due_date,
+            score = excluded.score, points = excluded.points, notes = excluded.notes, last_seen = excluded.last_seen",
+        params![student.district_url, student.user, period_name, class_name, key, assignment.name, assignment.category,
+            assignment.date.map(|d| d.to_iso_string()), assignment.due_date.map(|d| d.to_iso_string()),
+            assignment.score, assignment.points, assignment.notes, now],
+    )?;
+
+    let latest: Option<(String, String)> = tx.query_row(
+        "SELECT score, points FROM score_history
+        WHERE district_url = ?1 AND user = ?2 AND period_name = ?3 AND class_name = ?4 AND assignment_key = ?5
+        ORDER BY id DESC LIMIT 1",
+        params![student.district_url, student.user, period_name, class_name, key],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    ).optional()?;
+
+    if latest != Some((assignment.score.clone(), assignment.points.clone())) {
+        tx.execute(
+            "INSERT INTO score_history (district_url, user, period_name, class_name, assignment_key, score, points, recorded_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![student.district_url, student.user, period_name, class_name, key, assignment.score, assignment.points, now],
+        )?;
+    }
+
+    Ok(())
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::attendance::Period;
+    use quick_xml::de;
+
+    const STUDENT: (&str, &str) = ("https://sv.test", "student");
+
+    #[test]
+    fn resync_keeps_history() {
+        let mut store = Store::open_in_memory().unwrap();
+        assert_eq!(store.schema_version().unwrap(), MIGRATIONS.len());
+
+        let mut grades: crate::model::grade::GbData = de::from_str(include_str!("../tests/fixtures/gradebook_assignments.xml")).unwrap();
+        store.save_gradebook(STUDENT, &grades.gradebook).unwrap();
+        store.save_gradebook(STUDENT, &grades.gradebook).unwrap();
+
+        let courses = &mut grades.gradebook.courses.course;
+        let class_name = courses[0].class_name.clone();
+        let mark = &mut courses[0].marks[0].mark[0];
+        let id = mark.assignments.assignment[2].id.clone();
+        mark.assignments.assignment[2].points = String::from("91.00 / 100.0000");
+        mark.grade = String::from("B+");
+        courses.truncate(1);
+        store.save_gradebook(STUDENT, &grades.gradebook).unwrap();
+
+        let marks = store.mark_history(STUDENT, &class_name).unwrap();
+        assert_eq!(marks.iter().map(|m| m.grade.as_str()).collect::<Vec<_>>(), ["B", "B+"]);
+
+        let scores = store.score_history(STUDENT, &class_name, &id).unwrap();
+        assert_eq!(scores.len(), 2);
+        assert_eq!(scores[1].points, "91.00 / 100.0000");
+
+        let assignments: i64 = store.connection()
+            .query_row("SELECT COUNT(*) FROM assignments WHERE class_name = ?1", params![class_name], |row| row.get(0))
+            .unwrap();
+        assert_eq!(assignments, 3);
+
+        // courses dropped by the district stay queryable
+        assert!(store.courses(STUDENT).unwrap().contains(&String::from("Teacher Aide")));
+    }
+
+    #[test]
+    fn attendance_upserts() {
+        let mut store = Store::open_in_memory().unwrap();
+        let mut att: AttData = de::from_str(include_str!("../tests/fixtures/attendance_absence_without_note.xml")).unwrap();
+
+        store.save_attendance(STUDENT, &att).unwrap();
+        att.attendance.absences.absence[0].reason = String::from("Excused");
+        store.save_attendance(STUDENT, &att).unwrap();
+
+        let dates = store.absence_dates(STUDENT).unwrap();
+        assert_eq!(dates.len(), att.attendance.absences.absence.len());
+        let reason: String = store.connection()
+            .query_row("SELECT reason FROM absences WHERE date = ?1", params![dates[0].to_iso_string()], |row| row.get(0))
+            .unwrap();
+        assert_eq!(reason, att.attendance.absences.absence.iter().find(|a| a.absence_date == dates[0]).unwrap().reason);
+
+        // a resync with more periods updates the absence of that day
+        let absence = att.attendance.absences.absence.iter_mut().find(|a| a.absence_date == dates[0]).unwrap();
+        absence.periods.period.push(Period {
+            number: 6,
+            name: String::new(),
+            reason: String::from("Tardy"),
+            course: String::new(),
+            staff: String::new(),
+            staff_email: String::new(),
+            icon_name: String::new(),
+            school_name: String::new(),
+        });
+        store.save_attendance(STUDENT, &att).unwrap();
+
+        let (rows, periods): (i64, String) = store.connection()
+            .query_row("SELECT COUNT(*), periods FROM absences WHERE date = ?1", params![dates[0].to_iso_string()], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap();
+        assert_eq!((rows, periods.as_str()), (1, "6"));
+        assert_eq!(store.absence_dates(STUDENT).unwrap(), dates);
+    }
+
+    #[test]
+    fn districts_kept_apart() {
+        let mut store = Store::open_in_memory().unwrap();
+        let mut grades: crate::model::grade::GbData = de::from_str(include_str!("../tests/fixtures/gradebook_assignments.xml")).unwrap();
+        let other = ("https://other.test/", "student");
+
+        store.save_gradebook(STUDENT, &grades.gradebook).unwrap();
+        let class_name = grades.gradebook.courses.course[0].class_name.clone();
+        grades.gradebook.courses.course.truncate(1);
+        grades.gradebook.courses.course[0].marks[0].mark[0].grade = String::from("A");
+        store.save_gradebook(other, &grades.gradebook).unwrap();
+
+        let grades_of = |student| store.mark_history(student, &class_name).unwrap().into_iter().map(|m| m.grade).collect::<Vec<_>>();
+        assert_eq!(grades_of(STUDENT), ["B"]);
+        assert_eq!(grades_of(("https://other.test", "student")), ["A"]);
+        assert_eq!(store.courses(other).unwrap(), [class_name]);
+        assert!(store.courses(STUDENT).unwrap().len() > 1);
+    }
+
+    #[test]
+    fn newer_schema_rejected() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "user_version", MIGRATIONS.len() + 1).unwrap();
+
+        assert!(matches!(Store::from_connection(conn), Err(VueError::Storage(_))));
+    }
+}
@@ -0,0 +1,393 @@
+//! Polling watcher which streams changes to StudentVUE data
+//!
+//! `Watcher` polls the selected endpoints on a fixed interval, diffs every response with the previous one
+//! and yields the differences as a `Stream` of `WatchEvent`s. A random jitter is added to each interval so
+//! many watchers started together do not poll the district in lockstep, and the last response of every
+//! endpoint can be persisted to a state file so a restarted watcher does not report old changes again.
+//! The calendar is polled for the month of the current local date. A calendar of another month or a gradebook
+//! of another reporting period than the previous response starts a new baseline instead of being diffed.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use studentvue::{client::Client, watch::Watcher};
+//! use futures_util::StreamExt;
+//! use std::time::Duration;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let client = Client::create("https://studentvue.phoenixunion.org", "user", "pwd");
+//!     let mut events = Watcher::new(client, Duration::from_secs(15 * 60))
+//!         .with_jitter(Duration::from_secs(60))
+//!         .with_state_file("studentvue-watch.json")
+//!         .into_stream();
+//!
+//!     while let Some(event) = events.next().await {
+//!         match event {
+//!             Ok(event) => println!("{}", event),
+//!             Err(e) => eprintln!("{}", e),
+//!         }
+//!     }
+//! }
+//! ```
+
+use crate::{
+    client::{Client, ParamBuilder},
+    diff::*,
+    enums::{Method, ParamType},
+    error::VueError,
+    model::{attendance::AttData, calendar::StudentCalendar, date::Date, grade::GbData, message::PXPMessagesData},
+    request::{Transport, WebHandle},
+    retry::random_fraction,
+};
+use futures_util::stream::{self, BoxStream, StreamExt};
+use quick_xml::de;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fmt,
+    io,
+    path::PathBuf,
+    time::Duration,
+};
+
+/// Endpoints a `Watcher` can poll
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Endpoint {
+    Grades,
+    Attendance,
+    Messages,
+    Calendar,
+}
+
+impl Endpoint {
+    /// Every endpoint which can be watched
+    pub const ALL: [Endpoint; 4] = [Endpoint::Grades, Endpoint::Attendance, Endpoint::Messages, Endpoint::Calendar];
+
+    /// The `Method` polled for the endpoint
+    pub fn method(self) -> Method {
+        match self {
+            Endpoint::Grades => Method::GradeBook,
+            Endpoint::Attendance => Method::Attendance,
+            Endpoint::Messages => Method::GetPXPMessages,
+            Endpoint::Calendar => Method::StudentCalendar,
+        }
+    }
+}
+
+/// Change reported by a `Watcher`
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(tag = "endpoint", content = "change", rename_all = "snake_case")]
+pub enum WatchEvent {
+    Grade(GradeEvent),
+    Attendance(AttendanceEvent),
+    Message(MessageEvent),
+    Calendar(CalendarEvent),
+}
+
+impl fmt::Display for WatchEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WatchEvent::Grade(e) => e.fmt(f),
+            WatchEvent::Attendance(e) => e.fmt(f),
+            WatchEvent::Message(e) => e.fmt(f),
+            WatchEvent::Calendar(e) => e.fmt(f),
+        }
+    }
+}
+
+/// Periodically polls a `Client` and reports changes between consecutive responses
+pub struct Watcher<T: Transport = WebHandle> {
+    client: Client<T>,
+    endpoints: Vec<Endpoint>,
+    interval: Duration,
+    jitter: Duration,
+    state_file: Option<PathBuf>,
+}
+
+impl<T: Transport> Watcher<T> {
+    /// Creates a watcher polling every endpoint each `interval`
+    pub fn new(client: Client<T>, interval: Duration) -> Self {
+        Watcher {
+            client,
+            endpoints: Endpoint::ALL.to_vec(),
+            interval,
+            jitter: Duration::from_secs(0),
+            state_file: None,
+        }
+    }
+
+    /// Restricts the watcher to the given endpoints
+    pub fn with_endpoints(mut self, endpoints: &[Endpoint]) -> Self {
+        self.endpoints = endpoints.to_vec();
+        self
+    }
+
+    /// Adds a random delay of up to `jitter` to every interval
+    pub fn with_jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Persists the last response of every endpoint to `path`, restoring it when the stream starts
+    pub fn with_state_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.state_file = Some(path.into());
+        self
+    }
+
+    /// Starts polling, the first poll happens immediately and only records a baseline unless a previous
+    /// state was restored
+    ///
+    /// Request and parsing errors are yielded without ending the stream, the endpoint is retried on the next poll.
+    pub fn into_stream(self) -> BoxStream<'static, Result<WatchEvent, VueError>>
+    where
+        T: Send + Sync + 'static,
+        for<'a> T::Future<'a>: Send,
+    {
+        stream::unfold((self, None), |(watcher, snapshots)| async move {
+            let (snapshots, mut items) = match snapshots {
+                Some(snapshots) => {
+                    tokio::time::sleep(watcher.interval + random_delay(watcher.jitter)).await;
+                    (snapshots, Vec::new())
+                }
+                None => match watcher.load().await {
+                    Ok(snapshots) => (snapshots, Vec::new()),
+                    Err(e) => (HashMap::new(), vec![Err(e)]),
+                },
+            };
+
+            let snapshots = watcher.poll(snapshots, &mut items).await;
+            Some((stream::iter(items), (watcher, Some(snapshots))))
+        })
+        .flatten()
+        .boxed()
+    }
+
+    async fn poll(&self, mut snapshots: Snapshots, items: &mut Vec<Result<WatchEvent, VueError>>) -> Snapshots {
+        let mut changed = false;
+
+        for &endpoint in self.endpoints.iter() {
+            let xml = match self.fetch(endpoint).await {
+                Ok(xml) => xml,
+                Err(e) => {
+                    items.push(Err(e));
+                    continue;
+                }
+            };
+
+            let previous = snapshots.get(&endpoint).map(String::as_str);
+            if previous == Some(xml.as_str()) {
+                continue;
+            }
+
+            let events = match endpoint {
+                Endpoint::Grades => changes::<GbData, _>(previous, &xml, WatchEvent::Grade),
+                Endpoint::Attendance => changes::<AttData, _>(previous, &xml, WatchEvent::Attendance),
+                Endpoint::Messages => changes::<PXPMessagesData, _>(previous, &xml, WatchEvent::Message),
+                Endpoint::Calendar => changes::<StudentCalendar, _>(previous, &xml, WatchEvent::Calendar),
+            };
+
+            match events {
+                Ok(events) => {
+                    items.extend(events.into_iter().map(Ok));
+                    snapshots.insert(endpoint, xml);
+                    changed = true;
+                }
+                Err(e) => items.push(Err(e)),
+            }
+        }
+
+        if changed {
+            if let Err(e) = self.save(&snapshots).await {
+                items.push(Err(e));
+            }
+        }
+
+        snapshots
+    }
+
+    async fn fetch(&self, endpoint: Endpoint) -> Result<String, VueError> {
+        let parms = match endpoint {
+            Endpoint::Calendar => ParamBuilder::create().add_elements(&[ParamType::RequestDate(Date::local_today())])?,
+            _ => ParamBuilder::create(),
+        };
+
+        self.client.call_service(endpoint.method(), parms).await
+    }
+
+    async fn load(&self) -> Result<Snapshots, VueError> {
+        let path = match &self.state_file {
+            Some(path) => path,
+            None => return Ok(HashMap::new()),
+        };
+
+        match tokio::fs::read(path).await {
+            Ok(bytes) => Ok(serde_json::from_slice(&bytes).map_err(io::Error::from)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(HashMap::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    // Writes the state next to the state file and renames it over the file, so an interrupted write never
+    // leaves a truncated state behind
+    async fn save(&self, snapshots: &Snapshots) -> Result<(), VueError> {
+        if let Some(path) = &self.state_file {
+            let json = serde_json::to_vec(snapshots).map_err(io::Error::from)?;
+            let mut tmp = path.clone().into_os_string();
+            tmp.push(".tmp");
+
+            tokio::fs::write(&tmp, json).await?;
+            tokio::fs::rename(&tmp, path).await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Raw XML of the last successful response of each endpoint
+type Snapshots = HashMap<Endpoint, String>;
+
+/// Span of time covered by a response, diffing responses of different spans would report every item as new
+trait Period {
+    fn same_period(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Period for GbData {
+    fn same_period(&self, other: &Self) -> bool {
+        self.gradebook.reporting_period == other.gradebook.reporting_period
+    }
+}
+
+impl Period for StudentCalendar {
+    fn same_period(&self, other: &Self) -> bool {
+        self.calendar.month_begin_date == other.calendar.month_begin_date
+    }
+}
+
+impl Period for AttData {}
+
+impl Period for PXPMessagesData {}
+
+// Parses the new response and diffs it with the previous one, a missing or unreadable previous response, or
+// one of another period, only records a baseline
+fn changes<M, F>(previous: Option<&str>, xml: &str, wrap: F) -> Result<Vec<WatchEvent>, VueError>
+where
+    M: Diff + Period + DeserializeOwned,
+    F: Fn(M::Event) -> WatchEvent,
+{
+    let new: M = de::from_str(xml)?;
+    let old = previous
+        .and_then(|p| de::from_str::<M>(p).ok())
+        .filter(|old| old.same_period(&new));
+
+    Ok(old.map_or_else(Vec::new, |old| old.diff(&new).into_iter().map(wrap).collect()))
+}
+
+fn random_delay(max: Duration) -> Duration {
+    max.mul_f64(random_fraction())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::request::TransportFuture;
+    use std::sync::Mutex;
+
+    // Answers with the queued bodies in order, repeating the last one, and records the sent parameters
+    struct Replay(Mutex<Vec<&'static str>>, Mutex<Vec<String>>);
+
+    impl Transport for Replay {
+        type Future<'a> = TransportFuture<'a>;
+
+        fn post_form<'a>(&'a self, _uri: &'a str, params: &'a [(&'a str, &'a str)]) -> TransportFuture<'a> {
+            let parms = params.iter().find(|(k, _)| *k == "paramStr").unwrap().1;
+            self.1.lock().unwrap().push(parms.to_owned());
+            let mut bodies = self.0.lock().unwrap();
+            let body = if bodies.len() > 1 { bodies.remove(0) } else { bodies[0] };
+
+            Box::pin(async move { Ok(body.to_owned()) })
+        }
+    }
+
+    const MESSAGES: [&str; 3] = [
+        "<string><PXPMessagesData><MessageListings /></PXPMessagesData></string>",
+        "<string><PXPMessagesData><MessageListings>\
+            <MessageListing ID=\"1\" Subject=\"Picture day\" From=\"Front Office\" /></MessageListings></PXPMessagesData></string>",
+        "<string><PXPMessagesData><MessageListings>\
+            <MessageListing ID=\"1\" Subject=\"Picture day\" From=\"Front Office\" />\
+            <MessageListing ID=\"2\" Subject=\"Field trip\" From=\"Smith, Jane\" /></MessageListings></PXPMessagesData></string>",
+    ];
+
+    fn watcher(bodies: Vec<&'static str>, state_file: &PathBuf) -> Watcher<Replay> {
+        let client = Client::create_with_transport("https://sv.test", "student", "pwd", Replay(Mutex::new(bodies), Mutex::default()));
+
+        Watcher::new(client, Duration::from_millis(1))
+            .with_endpoints(&[Endpoint::Messages])
+            .with_state_file(state_file)
+    }
+
+    #[tokio::test]
+    async fn resumes_from_state() {
+        let state_file = std::env::temp_dir().join(format!("studentvue-watch-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&state_file);
+
+        let first: Vec<_> = watcher(MESSAGES[..2].to_vec(), &state_file)
+            .into_stream()
+            .take(1)
+            .collect()
+            .await;
+        assert_eq!(first[0].as_ref().unwrap().to_string(), "New message from Front Office: Picture day");
+
+        // the persisted response is the baseline, so only the second message is reported
+        let second: Vec<_> = watcher(MESSAGES[1..].to_vec(), &state_file)
+            .into_stream()
+            .take(1)
+            .collect()
+            .await;
+        assert_eq!(second[0].as_ref().unwrap().to_string(), "New message from Smith, Jane: Field trip");
+
+        std::fs::remove_file(&state_file).unwrap();
+    }
+
+    #[tokio::test]
+    async fn calendar_of_today() {
+        let state_file = std::env::temp_dir().join(format!("studentvue-watch-calendar-{}.json", std::process::id()));
+        let watcher = watcher(vec![include_str!("../tests/fixtures/calendar.xml")], &state_file)
+            .with_endpoints(&[Endpoint::Calendar]);
+        let mut items = Vec::new();
+        let snapshots = watcher.poll(HashMap::new(), &mut items).await;
+
+        assert!(items.is_empty() && snapshots.contains_key(&Endpoint::Calendar));
+        let parms = watcher.client.transport().1.lock().unwrap()[0].clone();
+        assert!(parms.contains(&format!("<RequestDate>{}</RequestDate>", Date::local_today())));
+
+        // the state is renamed into place, leaving no temporary file behind
+        assert_eq!(watcher.load().await.unwrap(), snapshots);
+        assert!(!state_file.with_extension("json.tmp").exists());
+        std::fs::remove_file(&state_file).unwrap();
+    }
+
+    #[test]
+    fn new_period_is_baseline() {
+        let calendar = include_str!("../tests/fixtures/calendar.xml");
+        let next_month = calendar
+            .replace("MonthBegDate=\"9/1/2019\" MonthEndDate=\"9/30/2019\"", "MonthBegDate=\"10/1/2019\" MonthEndDate=\"10/31/2019\"")
+            .replace("Labor Day", "Fall Break");
+        let same_month = calendar.replace("Labor Day", "Fall Break");
+
+        assert!(changes::<StudentCalendar, _>(Some(calendar), &next_month, WatchEvent::Calendar).unwrap().is_empty());
+        assert!(!changes::<StudentCalendar, _>(Some(calendar), &same_month, WatchEvent::Calendar).unwrap().is_empty());
+
+        let gradebook = include_str!("../tests/fixtures/gradebook_reporting_periods.xml");
+        let next_quarter = gradebook
+            .replace("<ReportingPeriod GradePeriod=\"Quarter 1\" StartDate=\"8/5/2019\" EndDate=\"10/4/2019\" />",
+                "<ReportingPeriod GradePeriod=\"Quarter 2\" StartDate=\"10/14/2019\" EndDate=\"12/20/2019\" />")
+            .replace("AP Chemistry", "Biology");
+        let same_quarter = gradebook.replace("AP Chemistry", "Biology");
+
+        assert!(changes::<GbData, _>(Some(gradebook), &next_quarter, WatchEvent::Grade).unwrap().is_empty());
+        assert!(!changes::<GbData, _>(Some(gradebook), &same_quarter, WatchEvent::Grade).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,289 @@
+//! Generic XML tree
+//!
+//! This module provides `Element`, an owned XML tree used for responses which have no typed model such as
+//! undocumented or newly added methods called through `Client::call_raw_tree`. Elements can be queried by
+//! path and converted into JSON.
+
+use crate::error::VueError;
+use quick_xml::{events::Event, DeError, Reader};
+use serde::Serialize;
+use std::fmt;
+
+/// XML element with its attributes, child elements and text content
+#[derive(Debug, Default, Clone, Serialize, PartialEq)]
+pub struct Element {
+    pub name: String,
+    /// Attributes in document order with their values unescaped
+    pub attributes: Vec<(String, String)>,
+    pub children: Vec<Element>,
+    /// Text content of the element, trimmed and unescaped
+    pub text: String,
+}
+
+impl Element {
+    /// Parses the root element of a document
+    pub fn parse(xml: &str) -> Result<Element, VueError> {
+        let mut reader = Reader::from_str(xml);
+        reader.trim_text(true);
+
+        let mut stack: Vec<Element> = Vec::new();
+        let mut buf = Vec::new();
+
+        loop {
+            match reader.read_event(&mut buf).map_err(DeError::Xml)? {
+                Event::Start(start) => stack.push(Element::start(&start, &reader)?),
+                Event::Empty(start) => {
+                    let element = Element::start(&start, &reader)?;
+                    match stack.last_mut() {
+                        Some(parent) => parent.children.push(element),
+                        None => return Ok(element),
+                    }
+                }
+                Event::End(_) => {
+                    let element = stack.pop().ok_or(DeError::End)?;
+                    match stack.last_mut() {
+                        Some(parent) => parent.children.push(element),
+                        None => return Ok(element),
+                    }
+                }
+                Event::Text(text) => {
+                    if let Some(element) = stack.last_mut() {
+                        element.text.push_str(&text.unescape_and_decode(&reader).map_err(DeError::Xml)?);
+                    }
+                }
+                Event::CData(text) => {
+                    if let Some(element) = stack.last_mut() {
+                        element.text.push_str(reader.decode(&text).map_err(DeError::Xml)?);
+                    }
+                }
+                Event::Eof => return Err(DeError::Eof.into()),
+                _ => {}
+            }
+
+            buf.clear();
+        }
+    }
+
+    fn start(start: &quick_xml::events::BytesStart, reader: &Reader<&[u8]>) -> Result<Element, VueError> {
+        let attributes = start.attributes()
+            .map(|attr| {
+                let attr = attr?;
+                Ok((reader.decode(attr.key)?.to_owned(), attr.unescape_and_decode_value(reader)?))
+            })
+            .collect::<Result<_, quick_xml::Error>>()
+            .map_err(DeError::Xml)?;
+
+        Ok(Element {
+            name: reader.decode(start.name()).map_err(DeError::Xml)?.to_owned(),
+            attributes,
+            ..Element::default()
+        })
+    }
+
+    /// Value of an attribute
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// First child element with the given name
+    pub fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|c| c.name == name)
+    }
+
+    /// Every child element with the given name
+    pub fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> {
+        self.children.iter().filter(move |c| c.name == name)
+    }
+
+    /// Every element matching a `/` separated path of element names relative to this element, `*` matches
+    /// any name
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use studentvue::xml::Element;
+    ///
+    /// let root = Element::parse("<string><Gradebook><Courses><Course Title=\"Chemistry\" /></Courses></Gradebook></string>").unwrap();
+    /// let courses = root.find_all("Gradebook/Courses/Course");
+    ///
+    /// assert_eq!(courses[0].attribute("Title"), Some("Chemistry"));
+    /// assert_eq!(root.value("*/Courses/Course/@Title"), Some("Chemistry"));
+    /// ```
+    pub fn find_all(&self, path: &str) -> Vec<&Element> {
+        path.split('/')
+            .filter(|segment| !segment.is_empty())
+            .fold(vec![self], |elements, segment| {
+                elements.into_iter()
+                    .flat_map(|e| e.children.iter())
+                    .filter(|c| segment == "*" || c.name == segment)
+                    .collect()
+            })
+    }
+
+    /// First element matching a path, see `find_all`
+    pub fn find(&self, path: &str) -> Option<&Element> {
+        self.find_all(path).into_iter().next()
+    }
+
+    /// Text of the first element matching a path, or an attribute of it when the path ends with `@Name`
+    pub fn value(&self, path: &str) -> Option<&str> {
+        let (path, attribute) = match path.rsplit_once('@') {
+            Some((path, attribute)) => (path, Some(attribute)),
+            None => (path, None),
+        };
+        let element = self.find(path)?;
+
+        match attribute {
+            Some(attribute) => element.attribute(attribute),
+            None => Some(element.text.as_str()),
+        }
+    }
+
+    /// Converts the element into JSON
+    ///
+    /// Attributes and child elements become fields named after them, repeated names are collected into an
+    /// array and text is stored under `#text`. An element holding only text becomes a string.
+    pub fn to_json(&self) -> serde_json::Value {
+        use serde_json::{map::Entry, Map, Value};
+
+        if self.attributes.is_empty() && self.children.is_empty() && !self.text.is_empty() {
+            return Value::String(self.text.clone());
+        }
+
+        let mut object = Map::new();
+        let fields = self.attributes.iter()
+            .map(|(key, value)| (key.clone(), Value::String(value.clone())))
+            .chain(self.children.iter().map(|c| (c.name.clone(), c.to_json())));
+
+        for (key, value) in fields {
+            match object.entry(key) {
+                Entry::Vacant(entry) => {
+                    entry.insert(value);
+                }
+                Entry::Occupied(mut entry) => match entry.get_mut() {
+                    Value::Array(values) => values.push(value),
+                    existing => *existing = Value::Array(vec![existing.take(), value]),
+                },
+            }
+        }
+
+        if !self.text.is_empty() {
+            object.insert(String::from("#text"), Value::String(self.text.clone()));
+        }
+
+        Value::Object(object)
+    }
+
+    fn write_indented(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        write!(f, "{:indent$}<{}", "", self.name, indent = depth * 2)?;
+        for (key, value) in self.attributes.iter() {
+            write!(f, " {}=\"{}\"", key, escape(value))?;
+        }
+
+        match (self.children.is_empty(), self.text.is_empty()) {
+            (true, true) => writeln!(f, " />"),
+            (true, false) => writeln!(f, ">{}</{}>", escape(&self.text), self.name),
+            _ => {
+                writeln!(f, ">")?;
+                if !self.text.is_empty() {
+                    writeln!(f, "{:indent$}{}", "", escape(&self.text), indent = depth * 2 + 2)?;
+                }
+                for child in self.children.iter() {
+                    child.write_indented(f, depth + 1)?;
+                }
+                writeln!(f, "{:indent$}</{}>", "", self.name, indent = depth * 2)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Element {
+    /// Formats the element as indented XML
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_indented(f, 0)
+    }
+}
+
+/// Whether `name` is a valid XML element name, following the `Name` production of the XML specification
+pub fn is_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(is_name_start) && chars.all(|c| is_name_start(c) || is_name_char(c))
+}
+
+fn is_name_start(c: char) -> bool {
+    matches!(c,
+        ':' | 'A'..='Z' | '_' | 'a'..='z' | '\u{C0}'..='\u{D6}' | '\u{D8}'..='\u{F6}' | '\u{F8}'..='\u{2FF}'
+        | '\u{370}'..='\u{37D}' | '\u{37F}'..='\u{1FFF}' | '\u{200C}'..='\u{200D}' | '\u{2070}'..='\u{218F}'
+        | '\u{2C00}'..='\u{2FEF}' | '\u{3001}'..='\u{D7FF}' | '\u{F900}'..='\u{FDCF}' | '\u{FDF0}'..='\u{FFFD}'
+        | '\u{10000}'..='\u{EFFFF}')
+}
+
+fn is_name_char(c: char) -> bool {
+    matches!(c, '-' | '.' | '0'..='9' | '\u{B7}' | '\u{300}'..='\u{36F}' | '\u{203F}'..='\u{2040}')
+}
+
+/// Escapes the characters which are not allowed in XML text and attribute values
+pub fn escape(raw: &str) -> String {
+    let mut escaped = String::with_capacity(raw.len());
+    for c in raw.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tree() {
+        let xml = "<string><StudentHealthInfo xmlns:xsd=\"http://www.w3.org/2001/XMLSchema\">\
+            <HealthVisitListings><HealthVisitListing Date=\"9/4/2019\" Reason=\"Headache &amp; fever\" /></HealthVisitListings>\
+            <Note><![CDATA[Allergic to <peanuts>]]></Note>\
+            </StudentHealthInfo></string>";
+        let root = Element::parse(xml).unwrap();
+        let health = root.child("StudentHealthInfo").unwrap();
+
+        assert_eq!(root.name, "string");
+        assert_eq!(health.attribute("xmlns:xsd"), Some("http://www.w3.org/2001/XMLSchema"));
+        assert_eq!(health.child("Note").unwrap().text, "Allergic to <peanuts>");
+
+        let visits: Vec<_> = health.child("HealthVisitListings").unwrap().children_named("HealthVisitListing").collect();
+        assert_eq!(visits[0].attribute("Reason"), Some("Headache & fever"));
+        assert!(health.to_string().contains("<HealthVisitListing Date=\"9/4/2019\" Reason=\"Headache &amp; fever\" />"));
+    }
+
+    #[test]
+    fn paths_and_json() {
+        let xml = "<string><StudentFee><Fees><Fee Desc=\"Lab\" Amount=\"15\" /><Fee Desc=\"Yearbook\" Amount=\"60\" /></Fees>\
+            <Total>75</Total><Notes /></StudentFee></string>";
+        let root = Element::parse(xml).unwrap();
+
+        assert_eq!(root.find_all("StudentFee/Fees/Fee").len(), 2);
+        assert_eq!(root.find_all("*/*/Fee").len(), 2);
+        assert!(root.find("StudentFee/Missing").is_none());
+        assert_eq!(root.value("StudentFee/Total"), Some("75"));
+        assert_eq!(root.value("StudentFee/Fees/Fee/@Desc"), Some("Lab"));
+
+        let json = root.find("StudentFee").unwrap().to_json();
+        assert_eq!(json["Fees"]["Fee"][1]["Desc"], "Yearbook");
+        assert_eq!(json["Total"], "75");
+        assert_eq!(json["Notes"], serde_json::json!({}));
+    }
+
+    #[test]
+    fn unterminated() {
+        assert!(Element::parse("<string><Gradebook>").is_err());
+        assert!(Element::parse("").is_err());
+    }
+}
@@ -0,0 +1,257 @@
+//! Edge case XML fixtures: synthetic responses modelled on those sent by districts, with made up data
+
+use quick_xml::de;
+use studentvue::model::{attendance, calendar, date::{Date, Time}, device, district, grade, message, schedule, school, student};
+
+#[test]
+fn gradebook_alphanumeric_rooms_and_marks() {
+    let grades: grade::GbData = de::from_str(include_str!("fixtures/gradebook_edge_cases.xml")).unwrap();
+    let courses = &grades.gradebook.courses.course;
+
+    assert_eq!(courses.len(), 3);
+    assert_eq!(courses[0].room, "204B");
+    assert_eq!(courses[0].marks[0].mark[0].grade, "A+");
+    assert_eq!(courses[0].marks[0].mark[0].percent, Some(98.7));
+    assert_eq!(courses[1].room, "CAFE");
+    assert_eq!(courses[1].marks[0].mark[0].grade, "N/A");
+    assert_eq!(courses[1].marks[0].mark[0].percent, None);
+    assert!(courses[2].marks[0].mark.is_empty());
+}
+
+#[test]
+fn gradebook_without_courses() {
+    let grades: grade::GbData = de::from_str(include_str!("fixtures/gradebook_no_courses.xml")).unwrap();
+    assert!(grades.gradebook.courses.course.is_empty());
+}
+
+#[test]
+fn attendance_without_absences() {
+    let empty: attendance::AttData = de::from_str(include_str!("fixtures/attendance_no_absences.xml")).unwrap();
+    assert!(empty.attendance.absences.absence.is_empty());
+
+    let missing: attendance::AttData = de::from_str(include_str!("fixtures/attendance_missing_absences.xml")).unwrap();
+    assert!(missing.attendance.absences.absence.is_empty());
+}
+
+#[test]
+fn attendance_absence_without_note() {
+    let att: attendance::AttData = de::from_str(include_str!("fixtures/attendance_absence_without_note.xml")).unwrap();
+    let absences = &att.attendance.absences.absence;
+
+    assert_eq!(absences.len(), 2);
+    assert_eq!(absences[0].absence_date, Date::new(2019, 9, 4).unwrap());
+    assert!(absences[0].absence_date < absences[1].absence_date);
+    assert_eq!(absences[0].note, "");
+    assert_eq!(absences[1].note, "Bus was late");
+}
+
+#[test]
+fn schedule_named_rooms() {
+    let sched: schedule::StudentClassSchedule = de::from_str(include_str!("fixtures/schedule_edge_cases.xml")).unwrap();
+    let classes = &sched.schedule.class_list.class;
+
+    assert_eq!(classes[0].room_name, "CAFE");
+    assert_eq!(classes[0].teacher, "");
+    assert_eq!(classes[1].room_name, "GYM-B");
+}
+
+#[test]
+fn schedule_without_classes() {
+    let sched: schedule::StudentClassSchedule = de::from_str(include_str!("fixtures/schedule_no_classes.xml")).unwrap();
+    assert!(sched.schedule.class_list.class.is_empty());
+}
+
+#[test]
+fn school_zip_plus_four() {
+    let info: school::StudentSchoolInfoListing = de::from_str(include_str!("fixtures/school_zip4.xml")).unwrap();
+
+    assert_eq!(info.school_info.school_zip, "85012-1234");
+    assert!(info.school_info.staff_list.staff.is_empty());
+}
+
+#[test]
+fn student_missing_contact_details() {
+    let student: student::Student = de::from_str(include_str!("fixtures/student_minimal.xml")).unwrap();
+
+    assert_eq!(student.student_info.name, "Jane Doe");
+    assert_eq!(student.student_info.email, "");
+    assert_eq!(student.student_info.birth_date, Date::new(2004, 1, 23));
+    assert_eq!(student.student_info.nickname, None);
+}
+
+#[test]
+fn attendance_period_detail() {
+    let att: attendance::AttData = de::from_str(include_str!("fixtures/attendance_periods.xml")).unwrap();
+    let attendance = &att.attendance;
+
+    assert_eq!(attendance.attendance_type, "Period");
+    assert_eq!(attendance.period_count, 8);
+    assert_eq!(attendance.period_entries().count(), 3);
+    assert_eq!(attendance.absences.absence[1].periods.period[1].course, "English 11");
+
+    let totals = attendance.course_totals();
+    assert_eq!(totals.len(), 2);
+    assert_eq!(totals[0].course, "AP Chemistry");
+    assert_eq!((totals[0].excused, totals[0].tardies), (1, 1));
+    assert_eq!(totals[1].course, "English 11");
+    assert_eq!((totals[1].excused, totals[1].tardies), (1, 0));
+}
+
+#[test]
+fn student_full_profile() {
+    let student: student::Student = de::from_str(include_str!("fixtures/student_full.xml")).unwrap();
+    let info = &student.student_info;
+
+    assert_eq!(info.nickname.as_deref(), Some("JD"));
+    assert_eq!(info.home_language, "English");
+    assert_eq!(info.counselor_name, "Lee, K");
+    assert_eq!(info.lockers.locker[0].locker_number, "1142");
+    assert_eq!(info.emergency_contacts.contact.len(), 2);
+    assert_eq!(info.emergency_contacts.contact[0].relationship, "Father");
+    assert_eq!(info.physician.as_ref().unwrap().hospital, "St. Joseph's");
+    assert_eq!(info.dentist.as_ref().unwrap().extension, "12");
+    assert_eq!(info.user_defined_groups.group[0].items.item[0].value, "12A");
+    assert_eq!(info.photo.as_deref(), Some(&[0xff, 0xd8, 0xff, 0xe0, 0x00, 0x10, 0x4a, 0x46, 0x49, 0x46][..]));
+}
+
+#[test]
+fn schedule_terms_times_and_concurrent_schools() {
+    let sched: schedule::StudentClassSchedule = de::from_str(include_str!("fixtures/schedule_full.xml")).unwrap();
+    let schedule = &sched.schedule;
+
+    assert_eq!(schedule.term_name, "Semester 2");
+    assert_eq!(schedule.class_list.class[0].section_gu, "5EC1");
+    assert_eq!(schedule.terms.term.len(), 2);
+    assert_eq!(schedule.current_term().unwrap().begin_date, Date::new(2020, 1, 6));
+
+    let today = schedule.today.as_ref().unwrap();
+    let classes = &today.schools.school[0].classes.class;
+    assert_eq!(today.date, Date::new(2020, 1, 14));
+    assert_eq!(classes[0].start_time, Time::new(7, 30));
+    assert_eq!(classes[1].end_time, Time::new(12, 40));
+    assert!(classes[0].end_time < classes[1].start_time);
+
+    let concurrent = &schedule.concurrent_schools.school[0];
+    assert_eq!(concurrent.org_year_gu, "0A11");
+    assert_eq!(concurrent.class_list.class[0].course_title, "Mandarin I");
+}
+
+#[test]
+fn gradebook_reporting_periods() {
+    let grades: grade::GbData = de::from_str(include_str!("fixtures/gradebook_reporting_periods.xml")).unwrap();
+    let gradebook = &grades.gradebook;
+
+    assert_eq!(gradebook.reporting_periods.period.len(), 3);
+    assert_eq!(gradebook.reporting_periods.period[2].name, "Quarter 2");
+    assert_eq!(gradebook.reporting_periods.period[2].start_date, Date::new(2019, 10, 14));
+    assert_eq!(gradebook.reporting_period.as_ref().unwrap().name, "Quarter 1");
+}
+
+#[test]
+fn gradebook_assignments_and_categories() {
+    let grades: grade::GbData = de::from_str(include_str!("fixtures/gradebook_assignments.xml")).unwrap();
+    let mark = grades.gradebook.courses.course[0].current_mark().unwrap();
+
+    assert_eq!(mark.name, "Quarter 1");
+    assert_eq!(mark.grade_calculation.category.len(), 3);
+    assert_eq!(mark.grade_calculation.category[0].weight, Some(60.0));
+    assert_eq!(mark.grade_calculation.category[0].weighted_percent, Some(48.0));
+
+    let assignments = &mark.assignments.assignment;
+    assert_eq!(assignments.len(), 3);
+    assert_eq!(assignments[1].category, "Homework");
+    assert_eq!(assignments[1].due_date, Date::new(2019, 9, 12));
+    assert_eq!((assignments[1].points_earned(), assignments[1].points_possible()), (Some(19.0), Some(20.0)));
+    assert_eq!((assignments[2].points_earned(), assignments[2].points_possible()), (None, Some(100.0)));
+}
+
+#[test]
+fn messages_with_attachments() {
+    let messages: message::PXPMessagesData = de::from_str(include_str!("fixtures/messages.xml")).unwrap();
+    let listing = &messages.messages.message_listings.message;
+
+    assert_eq!(listing.len(), 2);
+    assert_eq!(listing[0].id, "6b1f0c2e");
+    assert_eq!(listing[0].begin_date, Date::new(2019, 9, 14));
+    assert!(!listing[0].read);
+    assert_eq!(listing[0].attachments.attachment[0].name, "permission.pdf");
+    assert_eq!(listing[1].begin_date, None);
+    assert!(listing[1].read);
+    assert!(listing[1].attachments.attachment.is_empty());
+}
+
+#[test]
+fn calendar_events() {
+    let calendar = de::from_str::<calendar::StudentCalendar>(include_str!("fixtures/calendar.xml")).unwrap().calendar;
+    let events = &calendar.event_lists.event;
+
+    assert_eq!(calendar.month_begin_date, Date::new(2019, 9, 1));
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].start_time, Time::new(23, 59));
+    assert_eq!(events[0].assignment_gu, "1201");
+    assert_eq!(events[1].day_type, "Holiday");
+    assert_eq!(events[1].start_time, None);
+}
+
+// Parses a fixture and checks that the JSON the model serializes to deserializes back to the same model
+fn round_trip<T>(xml: &str)
+where
+    T: serde::Serialize + serde::de::DeserializeOwned + PartialEq + std::fmt::Debug,
+{
+    let model: T = de::from_str(xml).unwrap();
+    let json = serde_json::to_string(&model).unwrap();
+
+    assert_eq!(serde_json::from_str::<T>(&json).unwrap(), model, "{}", json);
+}
+
+#[test]
+fn gradebook_round_trip() {
+    round_trip::<grade::GbData>(include_str!("fixtures/gradebook_assignments.xml"));
+    round_trip::<grade::GbData>(include_str!("fixtures/gradebook_edge_cases.xml"));
+    round_trip::<grade::GbData>(include_str!("fixtures/gradebook_reporting_periods.xml"));
+}
+
+#[test]
+fn attendance_round_trip() {
+    round_trip::<attendance::AttData>(include_str!("fixtures/attendance_periods.xml"));
+    round_trip::<attendance::AttData>(include_str!("fixtures/attendance_absence_without_note.xml"));
+}
+
+#[test]
+fn schedule_round_trip() {
+    round_trip::<schedule::StudentClassSchedule>(include_str!("fixtures/schedule_full.xml"));
+    round_trip::<schedule::StudentClassSchedule>(include_str!("fixtures/schedule_edge_cases.xml"));
+}
+
+#[test]
+fn school_round_trip() {
+    round_trip::<school::StudentSchoolInfoListing>(include_str!("fixtures/school_zip4.xml"));
+}
+
+#[test]
+fn student_round_trip() {
+    round_trip::<student::Student>(include_str!("fixtures/student_full.xml"));
+    round_trip::<student::Student>(include_str!("fixtures/student_minimal.xml"));
+}
+
+#[test]
+fn messages_round_trip() {
+    round_trip::<message::PXPMessagesData>(include_str!("fixtures/messages.xml"));
+}
+
+#[test]
+fn calendar_round_trip() {
+    round_trip::<calendar::StudentCalendar>(include_str!("fixtures/calendar.xml"));
+}
+
+#[test]
+fn district_round_trip() {
+    round_trip::<district::MatchingDistricts>(r#"<string><DistrictLists><DistrictInfos>
+        <DistrictInfo DistrictID="9fd2" Name="Phoenix Union High School District" Address="Phoenix AZ 85012" PvueURL="https://studentvue.phoenixunion.org" />
+        </DistrictInfos></DistrictLists></string>"#);
+}
+
+#[test]
+fn device_round_trip() {
+    round_trip::<device::DeviceTokenResponse>(r#"<string><RT_RESPONSE RESPONSE_STATUS="Success" RESPONSE_MESSAGE="Device registered" /></string>"#);
+}